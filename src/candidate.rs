@@ -0,0 +1,192 @@
+use std::collections::{hash_map::Entry, HashMap};
+
+use chrono::NaiveDate;
+
+use crate::{
+    conflict::DateConflict, history::DateChange, parser::EarningsEvent,
+    symbol::SymbolNormalizer,
+};
+
+/// Evaluated candidates of one target date.
+#[derive(Debug)]
+pub struct DayCandidates {
+    pub date: NaiveDate,
+    /// Names of the websites which were parsed for the date.
+    pub attempted: Vec<&'static str>,
+    /// Names of the websites which were parsed successfully.
+    pub succeeded: Vec<&'static str>,
+    pub candidates: Vec<CompanyCandidate>,
+    /// Symbols whose date was changed by a website since earlier runs.
+    pub date_changes: Vec<DateChange>,
+    /// Symbols listed on other dates too, counted on this date.
+    pub date_conflicts: Vec<DateConflict>,
+}
+
+#[derive(Debug)]
+pub struct CompanyCandidate {
+    /// Event details merged from all websites listing the company.
+    pub event: EarningsEvent,
+    pub refs: usize,
+    /// Confidence based on the weights of the listing websites,
+    /// see [`crate::score::SourceWeights::score()`].
+    pub score: f64,
+    /// Events as listed by each of the websites, with their raw fields.
+    pub listings: Vec<EarningsEvent>,
+}
+
+impl CompanyCandidate {
+    fn new(symbol: String, event: EarningsEvent) -> Self {
+        let mut merged = event.clone();
+        merged.company.symbol = symbol;
+        Self {
+            event: merged,
+            refs: 1,
+            score: 0.0,
+            listings: vec![event],
+        }
+    }
+
+    /// Names of the websites listing the company, without repetition.
+    pub fn sources(&self) -> Vec<&'static str> {
+        let mut sources: Vec<&'static str> = Vec::new();
+        for listing in self.listings.iter() {
+            if !sources.contains(&listing.source) {
+                sources.push(listing.source);
+            }
+        }
+        sources
+    }
+
+    /// Symbols, as listed by the websites, which were normalized
+    /// into the symbol of the candidate.
+    pub fn merged_symbols(&self) -> Vec<&str> {
+        let mut symbols: Vec<&str> = Vec::new();
+        for listing in self.listings.iter() {
+            let symbol = listing.company.symbol.trim();
+            if symbol != self.event.company.symbol && !symbols.contains(&symbol)
+            {
+                symbols.push(symbol);
+            }
+        }
+        symbols
+    }
+}
+
+/// Evaluate candidates by data corelation. Events listing the same
+/// company, after normalizing their symbols with the `normalizer`,
+/// are merged into one candidate and every listing counts as
+/// a reference. Only candidates with at least `min_refs` references
+/// are returned.
+///
+/// Details missing from a listing are filled in from the following
+/// listings, in the order of `data`. Candidates are sorted by the
+/// number of references (descending) and then by their symbol.
+pub fn eval_candidates(
+    data: Vec<EarningsEvent>,
+    min_refs: usize,
+    normalizer: &SymbolNormalizer,
+) -> Vec<CompanyCandidate> {
+    let mut candidates: HashMap<String, CompanyCandidate> = HashMap::new();
+    for event in data {
+        let symbol = normalizer.normalize(&event.company.symbol);
+        match candidates.entry(symbol) {
+            Entry::Occupied(mut entry) => {
+                let candidate = entry.get_mut();
+                candidate.event.merge(&event);
+                candidate.refs += 1;
+                candidate.listings.push(event);
+            }
+            Entry::Vacant(entry) => {
+                let symbol = entry.key().clone();
+                entry.insert(CompanyCandidate::new(symbol, event));
+            }
+        }
+    }
+
+    let mut result: Vec<CompanyCandidate> = candidates
+        .into_values()
+        .filter(|c| c.refs >= min_refs)
+        .collect();
+    result.sort_by(|a, b| {
+        b.refs
+            .cmp(&a.refs)
+            .then_with(|| a.event.company.symbol.cmp(&b.event.company.symbol))
+    });
+    result
+}
+
+/// Listing of the company by the `source`, shared by the tests.
+#[cfg(test)]
+pub(crate) fn event(
+    source: &'static str,
+    symbol: &str,
+    name: &str,
+) -> EarningsEvent {
+    let company = crate::parser::Company {
+        symbol: symbol.to_owned(),
+        name: name.to_owned(),
+    };
+    EarningsEvent::new(source, company)
+}
+
+#[test]
+fn candidates_keep_their_sources() {
+    let data = vec![
+        event("Zacks", "AAPL", ""),
+        event("Benzinga", "MSFT", ""),
+        event("Investing", "AAPL", ""),
+    ];
+    let candidates = eval_candidates(data, 2, &SymbolNormalizer::default());
+    assert_eq!(candidates.len(), 1);
+    assert_eq!(candidates[0].event.company.symbol, "AAPL");
+    assert_eq!(candidates[0].sources(), ["Zacks", "Investing"]);
+}
+
+#[test]
+fn candidates_without_data() {
+    assert!(
+        eval_candidates(Vec::new(), 1, &SymbolNormalizer::default()).is_empty()
+    );
+}
+
+#[test]
+fn candidates_order_is_deterministic() {
+    let data = vec![
+        event("Zacks", "MSFT", ""),
+        event("Zacks", "NFLX", ""),
+        event("Benzinga", "AAPL", ""),
+        event("Investing", "NFLX", "Netflix"),
+        event("Investing", "msft ", "Microsoft"),
+    ];
+    let candidates = eval_candidates(data, 1, &SymbolNormalizer::default());
+    let summary: Vec<(&str, &str, usize)> = candidates
+        .iter()
+        .map(|c| {
+            let company = &c.event.company;
+            (company.symbol.as_str(), company.name.as_str(), c.refs)
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("MSFT", "Microsoft", 2),
+            ("NFLX", "Netflix", 2),
+            ("AAPL", "", 1),
+        ]
+    );
+}
+
+#[test]
+fn candidates_merge_symbol_notations() {
+    let data = vec![
+        event("Zacks", "BRK.B", "Berkshire Hathaway Inc."),
+        event("Investing", "BRK/B", ""),
+        event("TradingView", "NYSE:BRK-B", ""),
+        event("Benzinga", "BRK.B", ""),
+    ];
+    let candidates = eval_candidates(data, 1, &SymbolNormalizer::default());
+    assert_eq!(candidates.len(), 1);
+    assert_eq!(candidates[0].refs, 4);
+    assert_eq!(candidates[0].event.company.symbol, "BRK.B");
+    assert_eq!(candidates[0].merged_symbols(), ["BRK/B", "NYSE:BRK-B"]);
+}
@@ -1,5 +1,15 @@
+use std::path::PathBuf;
+
+use chrono::NaiveDate;
 use clap::{Arg, ArgAction, Command};
 
+use crate::{
+    history::DEFAULT_HISTORY,
+    output::{Format, DEFAULT_OUTPUT},
+    parser::Browser,
+    score,
+};
+
 pub fn cmd() -> Command {
     Command::new("Company Earnings Calendar Parser")
         .version("1.0")
@@ -8,6 +18,44 @@ pub fn cmd() -> Command {
             "Parses data about upcoming company \
             earnings from 5 different websites.",
         )
+        .subcommand_negates_reqs(true)
+        .subcommand(
+            Command::new("history")
+                .about(
+                    "Shows what each website listed for the symbol \
+                    in \nthe recorded runs.",
+                )
+                .arg(
+                    Arg::new("symbol")
+                        .help("Ticker symbol of the company.")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("days")
+                        .long("days")
+                        .value_name("N")
+                        .help("Only shows runs from the last N days.")
+                        .value_parser(clap::value_parser!(u64))
+                        .default_value("14"),
+                ),
+        )
+        .subcommand(
+            Command::new("stats")
+                .about(
+                    "Shows how many of the companies listed by each \
+                    \nwebsite reported on the listed date.",
+                )
+                .arg(
+                    Arg::new("days")
+                        .long("days")
+                        .value_name("N")
+                        .help(
+                            "Only counts earnings dates from the last N days.",
+                        )
+                        .value_parser(clap::value_parser!(u64))
+                        .default_value("90"),
+                ),
+        )
         .arg(
             Arg::new("refs")
                 .help(
@@ -17,6 +65,20 @@ pub fn cmd() -> Command {
                 .required(true)
                 .value_parser(clap::value_parser!(u8).range(..=5)),
         )
+        .arg(
+            Arg::new("min-score")
+                .long("min-score")
+                .value_name("SCORE")
+                .help(
+                    "Set the minimum confidence score needed for \
+                    each \ncompany (in range 0-1). The score is \
+                    the weight \nof the websites listing the \
+                    company divided by \nthe weight of all parsed \
+                    websites.",
+                )
+                .value_parser(score::parse_score)
+                .default_value("0"),
+        )
         .arg(
             Arg::new("preview")
                 .short('p')
@@ -27,6 +89,57 @@ pub fn cmd() -> Command {
                 )
                 .action(ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("jobs")
+                .short('j')
+                .long("jobs")
+                .value_name("N")
+                .help(
+                    "Parses up to N websites at the same time, each \
+                    \nin its own browser session (in range 1-5).",
+                )
+                .value_parser(clap::value_parser!(u8).range(1..=5))
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("browser")
+                .long("browser")
+                .help(
+                    "Browser to parse the websites with, overrides \
+                    \n'webdriver.browser' of the config file.",
+                )
+                .value_parser(Browser::NAMES),
+        )
+        .arg(
+            Arg::new("start-driver")
+                .long("start-driver")
+                .help(
+                    "Starts chromedriver (or geckodriver) on a free \
+                    port \nfor the run and stops it afterwards. \
+                    The binary is \nset with \
+                    'webdriver.chromedriver' \
+                    ('webdriver.geckodriver') \nin the config \
+                    file or found in PATH.",
+                )
+                .action(ArgAction::SetTrue)
+                .conflicts_with("replay"),
+        )
+        .arg(
+            Arg::new("no-browser")
+                .long("no-browser")
+                .help(
+                    "Only parses the websites which are fetched \
+                    without \na browser (Investing), so no \
+                    WebDriver is needed.",
+                )
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all([
+                    "preview",
+                    "browser",
+                    "start-driver",
+                    "replay",
+                ]),
+        )
         .arg(
             Arg::new("tdy")
                 .short('n')
@@ -38,7 +151,7 @@ pub fn cmd() -> Command {
                     calendar data from websites.",
                 )
                 .action(ArgAction::SetTrue)
-                .conflicts_with_all(["tmr", "yda"]),
+                .conflicts_with_all(["tmr", "yda", "date", "from"]),
         )
         .arg(
             Arg::new("tmr")
@@ -50,7 +163,7 @@ pub fn cmd() -> Command {
                     calendar data from websites.",
                 )
                 .action(ArgAction::SetTrue)
-                .conflicts_with_all(["tdy", "yda"]),
+                .conflicts_with_all(["tdy", "yda", "date", "from"]),
         )
         .arg(
             Arg::new("yda")
@@ -62,6 +175,188 @@ pub fn cmd() -> Command {
                     calendar data from websites.",
                 )
                 .action(ArgAction::SetTrue)
-                .conflicts_with_all(["tdy", "tmr"]),
+                .conflicts_with_all(["tdy", "tmr", "date", "from"]),
+        )
+        .arg(
+            Arg::new("date")
+                .short('d')
+                .long("date")
+                .value_name("YYYY-MM-DD")
+                .help(
+                    "Exclusive option. Sets the parser to parse \
+                    the \nscheduled earnings calendar data \
+                    for the given date.",
+                )
+                .value_parser(parse_date)
+                .conflicts_with("from"),
+        )
+        .arg(
+            Arg::new("from")
+                .long("from")
+                .value_name("YYYY-MM-DD")
+                .help(
+                    "Exclusive option. Sets the first date of \
+                    the range \nof dates to parse. \
+                    Requires '--to'.",
+                )
+                .value_parser(parse_date)
+                .requires("to"),
+        )
+        .arg(
+            Arg::new("to")
+                .long("to")
+                .value_name("YYYY-MM-DD")
+                .help("Sets the last date (inclusive) of the range.")
+                .value_parser(parse_date)
+                .requires("from"),
+        )
+        .arg(
+            Arg::new("week")
+                .short('w')
+                .long("week")
+                .help(
+                    "Parses every trading day (Mon-Fri) of the week \
+                    \ncontaining the chosen date. Websites showing \
+                    the \nwhole week on one page are only \
+                    navigated once.",
+                )
+                .action(ArgAction::SetTrue)
+                .conflicts_with("from"),
+        )
+        .arg(
+            Arg::new("replay")
+                .short('r')
+                .long("replay")
+                .value_name("DIR")
+                .help(
+                    "Parses saved pages from the directory instead \
+                    of \nstarting the WebDriver. Expects one \
+                    '<website>.html' \nfile per website \
+                    (e.g. 'marketwatch.html') inside of \na \
+                    'YYYY-MM-DD' directory for each date or \
+                    directly \ninside of DIR.",
+                )
+                .value_parser(clap::value_parser!(PathBuf))
+                .conflicts_with("preview"),
+        )
+        .arg(
+            Arg::new("capture")
+                .short('c')
+                .long("capture")
+                .value_name("DIR")
+                .help(
+                    "Saves the final page of every website into \
+                    a new \ntimestamped run directory inside of \
+                    DIR together \nwith a 'manifest.json' \
+                    describing the run.",
+                )
+                .value_parser(clap::value_parser!(PathBuf))
+                .conflicts_with("replay"),
+        )
+        .arg(
+            Arg::new("screenshots")
+                .long("screenshots")
+                .help("Also saves a screenshot of every captured page.")
+                .action(ArgAction::SetTrue)
+                .requires("capture"),
+        )
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .value_name("FILE")
+                .help(
+                    "Reads settings, like ticker symbol aliases, \
+                    from \nthe TOML file.",
+                )
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .help(
+                    "Format of the output file. 'json' also \
+                    includes \nthe run details and all \
+                    known event details, \n'csv' writes one \
+                    row per candidate and 'ics' \nan iCalendar \
+                    event per candidate.",
+                )
+                .value_parser(Format::NAMES)
+                .default_value("text"),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_name("PATH")
+                .help(
+                    "Path of the output file. '{date}', '{refs}', \
+                    '{time}' \nand '{ext}' are replaced with the \
+                    target date, \nminimum references, start \
+                    time of the run and \nextension of the \
+                    format. A directory keeps \nthe default \
+                    file name.",
+                )
+                .default_value(DEFAULT_OUTPUT),
+        )
+        .arg(
+            Arg::new("stdout")
+                .long("stdout")
+                .help("Prints the output instead of saving it to a file.")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["output", "no-clobber"]),
+        )
+        .arg(
+            Arg::new("no-clobber")
+                .long("no-clobber")
+                .help("Refuses to overwrite an already existing output file.")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("history")
+                .long("history")
+                .value_name("FILE")
+                .help(
+                    "SQLite database recording every run, the rows \
+                    \nparsed from each website and the evaluated \
+                    \ncandidates.",
+                )
+                .value_parser(clap::value_parser!(PathBuf))
+                .default_value(DEFAULT_HISTORY)
+                .global(true),
+        )
+        .arg(
+            Arg::new("no-history")
+                .long("no-history")
+                .help("Doesn't record the run in the history database.")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("diff")
+                .long("diff")
+                .help(
+                    "Shows the candidates added, removed or with \
+                    \nchanged references since the previous \
+                    recorded \nrun for the same date.",
+                )
+                .action(ArgAction::SetTrue)
+                .conflicts_with("no-history"),
         )
+        .arg(
+            Arg::new("learn-weights")
+                .long("learn-weights")
+                .help(
+                    "Weighs each website by the share of its listings \
+                    \nconfirmed by the reported earnings once 20 of \
+                    \nthem were reconciled, or agreed with by other \
+                    \nwebsites until then, instead of the configured \
+                    \nweights.",
+                )
+                .action(ArgAction::SetTrue)
+                .conflicts_with("no-history"),
+        )
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|e| format!("expected a YYYY-MM-DD date: {e}"))
 }
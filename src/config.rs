@@ -0,0 +1,100 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use serde::Deserialize;
+
+use crate::parser::Browser;
+
+/// Settings read from the TOML file passed with `--config`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Ticker symbols replaced with another symbol before matching
+    /// the listings of different websites, e.g. `GOOG = "GOOGL"`.
+    pub aliases: HashMap<String, String>,
+    /// Reliability weights of the websites used for scoring
+    /// the candidates, e.g. `Benzinga = 0.5`. The default is 1.
+    pub weights: HashMap<String, f64>,
+    pub webdriver: WebDriverConfig,
+}
+
+/// Settings of the browser automation, the `[webdriver]` table.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WebDriverConfig {
+    /// WebDriver endpoint, e.g. of a Selenium Grid. Not used with
+    /// `--start-driver`.
+    pub url: Option<String>,
+    pub browser: Browser,
+    /// Chromedriver binary started with `--start-driver`, found
+    /// in `PATH` if not set.
+    pub chromedriver: Option<PathBuf>,
+    /// Same as `chromedriver` for Firefox.
+    pub geckodriver: Option<PathBuf>,
+    pub user_agent: Option<String>,
+    /// Browser window width and height in pixels.
+    pub window_size: Option<[u32; 2]>,
+    /// Proxy `host:port` used for HTTP and HTTPS connections.
+    pub proxy: Option<String>,
+    /// Additional Chrome command line arguments.
+    pub chrome_args: Vec<String>,
+}
+
+impl WebDriverConfig {
+    /// Configured driver binary of the browser.
+    pub fn driver_path(&self) -> Option<&Path> {
+        match self.browser {
+            Browser::Chrome => self.chromedriver.as_deref(),
+            Browser::Firefox => self.geckodriver.as_deref(),
+        }
+    }
+}
+
+impl Config {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let config = std::fs::read_to_string(path).with_context(|| {
+            format!("Couldn't read the config file '{}'", path.display())
+        })?;
+        toml::from_str(&config).with_context(|| {
+            format!("Invalid config file '{}'", path.display())
+        })
+    }
+}
+
+#[test]
+fn parse_config() {
+    let config: Config = toml::from_str(
+        r#"
+        [aliases]
+        GOOG = "GOOGL"
+        "BF-A" = "BF.B"
+
+        [weights]
+        Benzinga = 0.5
+
+        [webdriver]
+        url = "http://grid:4444"
+        browser = "firefox"
+        chromedriver = "/opt/chromedriver"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.aliases["GOOG"], "GOOGL");
+    assert_eq!(config.aliases["BF-A"], "BF.B");
+    assert_eq!(config.weights["Benzinga"], 0.5);
+    assert_eq!(config.webdriver.url.as_deref(), Some("http://grid:4444"));
+    assert_eq!(config.webdriver.browser, Browser::Firefox);
+    assert_eq!(
+        config.webdriver.chromedriver.as_deref(),
+        Some(Path::new("/opt/chromedriver"))
+    );
+    assert_eq!(config.webdriver.driver_path(), None);
+
+    let config: Config = toml::from_str("").unwrap();
+    assert!(config.aliases.is_empty());
+    assert!(config.webdriver.chromedriver.is_none());
+    assert_eq!(config.webdriver.browser, Browser::Chrome);
+}
@@ -0,0 +1,164 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::{parser::DayData, symbol::SymbolNormalizer};
+
+/// Maximum number of days between listings of the same report.
+/// Listings further apart belong to reports of different quarters.
+const MAX_CONFLICT_DAYS: i64 = 30;
+
+/// Symbol listed on different dates by the websites.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DateConflict {
+    pub symbol: String,
+    /// Websites with the date they list the symbol on.
+    pub listings: Vec<(&'static str, NaiveDate)>,
+    /// Date the listings are counted on.
+    pub chosen: NaiveDate,
+}
+
+/// Finds symbols which the websites disagree on the date of, i.e. with
+/// no date listed by all websites listing the symbol, and moves their
+/// listings to the date listed by the most websites (the earliest one
+/// on a tie), so they're evaluated as one candidate instead of
+/// several weak ones. Every website counts once: its listings on
+/// other dates are dropped when it also lists the chosen date, since
+/// a website listing a symbol twice usually kept a stale row.
+pub fn resolve_conflicts(
+    days: &mut [DayData],
+    normalizer: &SymbolNormalizer,
+) -> Vec<DateConflict> {
+    // Websites and dates of every listing of each symbol.
+    let mut listings: BTreeMap<String, Vec<(&'static str, NaiveDate)>> =
+        BTreeMap::new();
+    for day in days.iter() {
+        for event in day.events.iter() {
+            let symbol = normalizer.normalize(&event.company.symbol);
+            listings
+                .entry(symbol)
+                .or_default()
+                .push((event.source, day.date));
+        }
+    }
+
+    let mut conflicts = Vec::new();
+    for (symbol, mut listings) in listings {
+        listings.sort_by_key(|&(source, date)| (date, source));
+        listings.dedup();
+        for report in listings.chunk_by(|(_, a), (_, b)| {
+            (*b - *a).num_days() <= MAX_CONFLICT_DAYS
+        }) {
+            let sources: BTreeSet<&str> =
+                report.iter().map(|(source, _)| *source).collect();
+            let agreed = report
+                .chunk_by(|(_, a), (_, b)| a == b)
+                .any(|listings| listings.len() == sources.len());
+            if agreed {
+                continue;
+            }
+            conflicts.push(DateConflict {
+                symbol: symbol.clone(),
+                listings: report.to_vec(),
+                chosen: majority_date(report),
+            });
+        }
+    }
+
+    for conflict in conflicts.iter() {
+        // Websites already counted on the chosen date.
+        let mut counted: BTreeSet<&str> = conflict
+            .listings
+            .iter()
+            .filter(|(_, date)| *date == conflict.chosen)
+            .map(|(source, _)| *source)
+            .collect();
+        let mut moved = Vec::new();
+        for day in days.iter_mut() {
+            if day.date == conflict.chosen
+                || !conflict.listings.iter().any(|(_, d)| *d == day.date)
+            {
+                continue;
+            }
+            let (conflicting, rest): (Vec<_>, Vec<_>) =
+                day.events.drain(..).partition(|e| {
+                    normalizer.normalize(&e.company.symbol) == conflict.symbol
+                });
+            day.events = rest;
+            moved.extend(
+                conflicting.into_iter().filter(|e| counted.insert(e.source)),
+            );
+        }
+        if let Some(day) = days.iter_mut().find(|d| d.date == conflict.chosen) {
+            day.events.extend(moved);
+        }
+    }
+    conflicts
+}
+
+/// Date with the most listings, the earliest one on a tie.
+/// The `listings` must be sorted by date and without duplicates.
+fn majority_date(listings: &[(&'static str, NaiveDate)]) -> NaiveDate {
+    let mut best = (0, listings[0].1);
+    for group in listings.chunk_by(|(_, a), (_, b)| a == b) {
+        if group.len() > best.0 {
+            best = (group.len(), group[0].1);
+        }
+    }
+    best.1
+}
+
+#[test]
+fn conflicting_dates_are_merged() {
+    use crate::candidate::event;
+
+    let date = |day| NaiveDate::from_ymd_opt(2024, 5, day).unwrap();
+    let day = |day, listings: &[(&'static str, &str)]| DayData {
+        date: date(day),
+        events: listings
+            .iter()
+            .map(|&(source, symbol)| event(source, symbol, ""))
+            .collect(),
+        attempted: Vec::new(),
+        succeeded: Vec::new(),
+    };
+    let mut days = [
+        day(21, &[("Zacks", "NVDA"), ("Zacks", "WMT"), ("Zacks", "LOW")]),
+        day(22, &[("Benzinga", "NVDA"), ("Investing", "NVDA")]),
+        day(23, &[("Benzinga", "NVDA"), ("Zacks", "LOW")]),
+    ];
+    let conflicts = resolve_conflicts(&mut days, &SymbolNormalizer::default());
+
+    assert_eq!(
+        conflicts,
+        [DateConflict {
+            symbol: "NVDA".to_owned(),
+            listings: vec![
+                ("Zacks", date(21)),
+                ("Benzinga", date(22)),
+                ("Investing", date(22)),
+                ("Benzinga", date(23)),
+            ],
+            chosen: date(22),
+        }]
+    );
+    let symbols = |day: &DayData| -> Vec<String> {
+        day.events
+            .iter()
+            .map(|e| e.company.symbol.clone())
+            .collect()
+    };
+    // Each website counts once on the chosen date.
+    assert_eq!(symbols(&days[0]), ["WMT", "LOW"]);
+    assert_eq!(symbols(&days[1]), ["NVDA", "NVDA", "NVDA"]);
+    // A symbol listed twice by one website isn't a conflict.
+    assert_eq!(symbols(&days[2]), ["LOW"]);
+}
+
+#[test]
+fn majority_tie_picks_earliest() {
+    let date = |day| NaiveDate::from_ymd_opt(2024, 5, day).unwrap();
+    let listings = [("Zacks", date(21)), ("Benzinga", date(22))];
+    assert_eq!(majority_date(&listings), date(21));
+}
@@ -0,0 +1,93 @@
+use std::collections::BTreeMap;
+
+use crate::{candidate::CompanyCandidate, history::RecordedCandidate};
+
+/// Changes of the candidates of one date since a previous run.
+#[derive(Debug, Default, PartialEq)]
+pub struct CandidateDiff {
+    /// Newly listed symbols with their references.
+    pub added: Vec<(String, usize)>,
+    /// Symbols no longer listed with their previous references.
+    pub removed: Vec<(String, usize)>,
+    /// Symbols with their previous and current references.
+    pub changed: Vec<(String, usize, usize)>,
+}
+
+impl CandidateDiff {
+    /// Compares the `current` candidates with the `previous` ones.
+    /// All sections are sorted by symbol.
+    pub fn new(
+        previous: &[RecordedCandidate],
+        current: &[CompanyCandidate],
+    ) -> Self {
+        let mut previous: BTreeMap<&str, usize> = previous
+            .iter()
+            .map(|c| (c.symbol.as_str(), c.refs))
+            .collect();
+        let mut diff = Self::default();
+        for candidate in current {
+            let symbol = candidate.event.company.symbol.as_str();
+            match previous.remove(symbol) {
+                None => diff.added.push((symbol.to_owned(), candidate.refs)),
+                Some(refs) if refs != candidate.refs => {
+                    diff.changed.push((symbol.to_owned(), refs, candidate.refs))
+                }
+                Some(_) => {}
+            }
+        }
+        diff.removed = previous
+            .into_iter()
+            .map(|(symbol, refs)| (symbol.to_owned(), refs))
+            .collect();
+        diff.added.sort();
+        diff.changed.sort();
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+    }
+}
+
+#[test]
+fn diff_sections() {
+    use crate::{
+        candidate::{eval_candidates, event},
+        symbol::SymbolNormalizer,
+    };
+
+    let current = eval_candidates(
+        vec![
+            event("Zacks", "NFLX", ""),
+            event("Zacks", "JNJ", ""),
+            event("Benzinga", "JNJ", ""),
+            event("Zacks", "TSLA", ""),
+            event("Zacks", "AAPL", ""),
+        ],
+        1,
+        &SymbolNormalizer::default(),
+    );
+    let recorded = |symbol: &str, refs| RecordedCandidate {
+        symbol: symbol.to_owned(),
+        refs,
+    };
+    let previous = [
+        recorded("NFLX", 1),
+        recorded("JNJ", 1),
+        recorded("IBKR", 2),
+        recorded("BAC", 1),
+    ];
+
+    let diff = CandidateDiff::new(&previous, &current);
+    assert_eq!(
+        diff,
+        CandidateDiff {
+            added: vec![("AAPL".to_owned(), 1), ("TSLA".to_owned(), 1)],
+            removed: vec![("BAC".to_owned(), 1), ("IBKR".to_owned(), 2)],
+            changed: vec![("JNJ".to_owned(), 1, 2)],
+        }
+    );
+    assert!(CandidateDiff::new(&[], &[]).is_empty());
+}
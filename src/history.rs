@@ -0,0 +1,812 @@
+use std::{collections::HashMap, path::Path};
+
+use anyhow::Context;
+use chrono::{DateTime, Local, NaiveDate};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use crate::{
+    candidate::DayCandidates, parser::DayData, symbol::SymbolNormalizer,
+};
+
+/// History database used when no `--history` is given.
+pub const DEFAULT_HISTORY: &str = "earnings_history.db";
+/// Maximum number of days an earnings date can move. Listings further
+/// apart belong to reports of different quarters.
+const MAX_DATE_CHANGE_DAYS: u32 = 30;
+
+/// Statements creating the tables of each schema version. The version
+/// of an existing database is kept in its `user_version`.
+const MIGRATIONS: [&str; 3] = [
+    "
+    CREATE TABLE runs (
+        id INTEGER PRIMARY KEY,
+        started TEXT NOT NULL,
+        replay INTEGER NOT NULL,
+        min_refs INTEGER NOT NULL
+    );
+    CREATE TABLE run_sources (
+        run_id INTEGER NOT NULL REFERENCES runs(id),
+        target_date TEXT NOT NULL,
+        source TEXT NOT NULL,
+        success INTEGER NOT NULL
+    );
+    CREATE TABLE rows (
+        run_id INTEGER NOT NULL REFERENCES runs(id),
+        target_date TEXT NOT NULL,
+        source TEXT NOT NULL,
+        symbol TEXT NOT NULL,
+        raw_symbol TEXT NOT NULL,
+        name TEXT NOT NULL,
+        timing TEXT NOT NULL,
+        fiscal_period TEXT,
+        eps_estimate TEXT,
+        revenue_estimate TEXT,
+        market_cap TEXT
+    );
+    CREATE INDEX rows_symbol ON rows(symbol, target_date);
+    CREATE TABLE candidates (
+        run_id INTEGER NOT NULL REFERENCES runs(id),
+        target_date TEXT NOT NULL,
+        symbol TEXT NOT NULL,
+        name TEXT NOT NULL,
+        refs INTEGER NOT NULL,
+        sources TEXT NOT NULL,
+        timing TEXT NOT NULL
+    );
+    CREATE INDEX candidates_date ON candidates(target_date, run_id);
+",
+    "ALTER TABLE candidates ADD COLUMN score REAL;",
+    "
+    ALTER TABLE rows ADD COLUMN eps_reported TEXT;
+    CREATE TABLE outcomes (
+        target_date TEXT NOT NULL,
+        symbol TEXT NOT NULL,
+        reported INTEGER NOT NULL,
+        PRIMARY KEY (target_date, symbol)
+    );
+",
+];
+
+/// Rows listed by runs started on or before their target date (in UTC),
+/// i.e. the predictions of the websites. Runs replaying snapshots
+/// are skipped here and in all other queries, since their pages can
+/// be from any time.
+const PREDICTIONS: &str = "
+    SELECT DISTINCT rows.source, rows.target_date, rows.symbol
+    FROM rows JOIN runs ON runs.id = rows.run_id
+    WHERE date(runs.started) <= rows.target_date AND runs.replay = 0";
+
+/// Rows listing the reported EPS in runs started after their target
+/// date (in UTC), once the companies reporting after the close have
+/// reported too. The dates of these rows were covered by a website
+/// showing the reported EPS.
+const REPORTED: &str = "
+    SELECT rows.target_date, rows.symbol
+    FROM rows JOIN runs ON runs.id = rows.run_id
+    WHERE rows.eps_reported IS NOT NULL
+    AND date(runs.started) > rows.target_date AND runs.replay = 0";
+
+/// SQLite database recording every run, the rows parsed from each
+/// website and the evaluated candidates, keyed by the target date.
+pub struct History {
+    conn: Connection,
+}
+
+/// Listing of a symbol by one website in one of the recorded runs.
+#[derive(Debug, PartialEq)]
+pub struct SymbolListing {
+    pub run_started: DateTime<Local>,
+    pub source: String,
+    pub target_date: NaiveDate,
+    pub timing: String,
+}
+
+/// Earnings date of a symbol changed by a website since
+/// an earlier run.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DateChange {
+    pub symbol: String,
+    pub source: String,
+    /// Date listed by the earlier run.
+    pub previous_date: NaiveDate,
+    /// Date listed by the current run.
+    pub date: NaiveDate,
+    /// Start of the earlier run.
+    pub previously_listed: DateTime<Local>,
+}
+
+/// Candidates recorded by a run for one target date.
+#[derive(Debug)]
+pub struct RecordedRun {
+    pub started: DateTime<Local>,
+    pub min_refs: usize,
+    pub candidates: Vec<RecordedCandidate>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedCandidate {
+    pub symbol: String,
+    pub refs: usize,
+}
+
+/// Predictions of one website on the reconciled dates.
+#[derive(Debug, PartialEq)]
+pub struct SourceStats {
+    pub source: String,
+    /// Symbols listed on a date before the date passed.
+    pub predicted: usize,
+    /// Predicted symbols which reported on the date.
+    pub confirmed: usize,
+    /// Symbols which reported on the dates, listed by any website.
+    pub reported: usize,
+}
+
+impl SourceStats {
+    /// Share of the predictions which were confirmed.
+    pub fn precision(&self) -> f64 {
+        ratio(self.confirmed, self.predicted)
+    }
+
+    /// Share of the reports which the website predicted.
+    pub fn recall(&self) -> f64 {
+        ratio(self.confirmed, self.reported)
+    }
+}
+
+fn ratio(part: usize, total: usize) -> f64 {
+    if total > 0 {
+        part as f64 / total as f64
+    } else {
+        0.0
+    }
+}
+
+impl History {
+    /// Opens the database at `path`, creating or upgrading
+    /// its tables if needed.
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let conn = Connection::open(path).with_context(|| {
+            format!("Couldn't open the history database '{}'", path.display())
+        })?;
+        let version: usize =
+            conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            conn.execute_batch(migration)?;
+            conn.pragma_update(None, "user_version", i + 1)?;
+        }
+        Ok(Self { conn })
+    }
+
+    /// Records a new run and returns its id.
+    pub fn start_run(
+        &self,
+        started: DateTime<Local>,
+        replay: bool,
+        min_refs: usize,
+    ) -> anyhow::Result<i64> {
+        self.conn.execute(
+            "INSERT INTO runs (started, replay, min_refs) VALUES (?1, ?2, ?3)",
+            params![started, replay, min_refs],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Records the parsed websites and all rows parsed for one date,
+    /// with their symbols normalized by the `normalizer`.
+    pub fn record_day(
+        &mut self,
+        run_id: i64,
+        day: &DayData,
+        normalizer: &SymbolNormalizer,
+    ) -> anyhow::Result<()> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut insert_source = tx.prepare(
+                "INSERT INTO run_sources (run_id, target_date, source, success)
+                VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for source in day.attempted.iter() {
+                let success = day.succeeded.contains(source);
+                insert_source
+                    .execute(params![run_id, day.date, source, success])?;
+            }
+
+            let mut insert_row = tx.prepare(
+                "INSERT INTO rows (run_id, target_date, source, symbol,
+                raw_symbol, name, timing, fiscal_period, eps_estimate,
+                revenue_estimate, market_cap, eps_reported)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            )?;
+            for event in day.events.iter() {
+                insert_row.execute(params![
+                    run_id,
+                    day.date,
+                    event.source,
+                    normalizer.normalize(&event.company.symbol),
+                    event.company.symbol,
+                    event.company.name,
+                    event.timing.abbreviation(),
+                    event.fiscal_period,
+                    event.eps_estimate,
+                    event.revenue_estimate,
+                    event.market_cap,
+                    event.eps_reported,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Records the evaluated candidates of one date.
+    pub fn record_candidates(
+        &mut self,
+        run_id: i64,
+        day: &DayCandidates,
+    ) -> anyhow::Result<()> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO candidates (run_id, target_date, symbol, name,
+                refs, sources, timing, score)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?;
+            for candidate in day.candidates.iter() {
+                let event = &candidate.event;
+                insert.execute(params![
+                    run_id,
+                    day.date,
+                    event.company.symbol,
+                    event.company.name,
+                    candidate.refs,
+                    candidate.sources().join(";"),
+                    event.timing.abbreviation(),
+                    candidate.score,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Returns the share of the rows of each website that another
+    /// website also listed for the same date in the same run, keyed by
+    /// the website name. Used as learned weights of the websites.
+    pub fn agreement_rates(&self) -> anyhow::Result<HashMap<String, f64>> {
+        let mut statement = self.conn.prepare(
+            "SELECT rows.source, avg(EXISTS (
+                SELECT 1 FROM rows other
+                WHERE other.run_id = rows.run_id
+                AND other.target_date = rows.target_date
+                AND other.symbol = rows.symbol
+                AND other.source != rows.source
+            ))
+            FROM rows JOIN runs ON runs.id = rows.run_id
+            WHERE runs.replay = 0 GROUP BY rows.source",
+        )?;
+        let rates = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+        Ok(rates)
+    }
+
+    /// Marks the predictions of every date before `today` as confirmed
+    /// or missed, once a website showing the reported EPS was parsed
+    /// for the date after it passed. Symbols with a reported EPS are
+    /// confirmed, all other symbols predicted for the date are missed.
+    /// Outcomes of dates which aren't covered that way, e.g. recorded
+    /// by an earlier version, are removed. Returns the number of
+    /// reconciled dates.
+    pub fn reconcile(&self, today: NaiveDate) -> anyhow::Result<usize> {
+        self.conn.execute(
+            &format!(
+                "DELETE FROM outcomes WHERE target_date NOT IN (
+                    SELECT target_date FROM ({REPORTED})
+                )"
+            ),
+            [],
+        )?;
+        self.conn.execute(
+            &format!(
+                "INSERT OR REPLACE INTO outcomes (target_date, symbol, reported)
+                SELECT target_date, symbol, max(reported) FROM (
+                    SELECT target_date, symbol, 1 AS reported
+                    FROM ({REPORTED})
+                    UNION ALL
+                    SELECT target_date, symbol, 0 FROM ({PREDICTIONS})
+                )
+                WHERE target_date < ?1 AND target_date IN (
+                    SELECT target_date FROM ({REPORTED})
+                )
+                GROUP BY target_date, symbol"
+            ),
+            params![today],
+        )?;
+        let dates = self.conn.query_row(
+            "SELECT count(DISTINCT target_date) FROM outcomes
+            WHERE target_date < ?1",
+            params![today],
+            |row| row.get(0),
+        )?;
+        Ok(dates)
+    }
+
+    /// Returns the precision and recall of each website on the dates
+    /// reconciled since the `since` date, sorted by the website name.
+    pub fn source_stats(
+        &self,
+        since: NaiveDate,
+    ) -> anyhow::Result<Vec<SourceStats>> {
+        let reported: usize = self.conn.query_row(
+            "SELECT count(*) FROM outcomes
+            WHERE reported AND target_date >= ?1",
+            params![since],
+            |row| row.get(0),
+        )?;
+        let mut statement = self.conn.prepare(&format!(
+            "SELECT predictions.source, count(*), sum(outcomes.reported)
+            FROM ({PREDICTIONS}) predictions JOIN outcomes
+            ON outcomes.target_date = predictions.target_date
+            AND outcomes.symbol = predictions.symbol
+            WHERE predictions.target_date >= ?1
+            GROUP BY predictions.source ORDER BY predictions.source"
+        ))?;
+        let stats = statement
+            .query_map(params![since], |row| {
+                Ok(SourceStats {
+                    source: row.get(0)?,
+                    predicted: row.get(1)?,
+                    confirmed: row.get(2)?,
+                    reported,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(stats)
+    }
+
+    /// Returns the candidates of the latest run, other than
+    /// the `current` one, which parsed the target `date`.
+    /// Runs replaying snapshots are skipped.
+    pub fn previous_run(
+        &self,
+        date: NaiveDate,
+        current: Option<i64>,
+    ) -> anyhow::Result<Option<RecordedRun>> {
+        let run = self.conn.query_row(
+            "SELECT runs.id, runs.started, runs.min_refs
+            FROM runs JOIN run_sources ON run_sources.run_id = runs.id
+            WHERE run_sources.target_date = ?1 AND runs.id != ?2
+            AND runs.replay = 0
+            ORDER BY runs.id DESC LIMIT 1",
+            params![date, current.unwrap_or(-1)],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, DateTime<Local>>(1)?,
+                    row.get::<_, usize>(2)?,
+                ))
+            },
+        );
+        let (id, started, min_refs) = match run {
+            Ok(run) => run,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let mut statement = self.conn.prepare(
+            "SELECT symbol, refs FROM candidates
+            WHERE run_id = ?1 AND target_date = ?2",
+        )?;
+        let candidates = statement
+            .query_map(params![id, date], |row| {
+                Ok(RecordedCandidate {
+                    symbol: row.get(0)?,
+                    refs: row.get(1)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(Some(RecordedRun {
+            started,
+            min_refs,
+            candidates,
+        }))
+    }
+
+    /// Returns the symbols listed for the target `date` by the `run_id`
+    /// run whose website listed them on another date in its latest
+    /// earlier run listing them. Dates the website still lists
+    /// the symbol on in the current run aren't considered changed,
+    /// and runs replaying snapshots aren't earlier runs.
+    pub fn date_changes(
+        &self,
+        run_id: i64,
+        date: NaiveDate,
+    ) -> anyhow::Result<Vec<DateChange>> {
+        let mut statement = self.conn.prepare(
+            "WITH previous AS (
+                SELECT cur.symbol, cur.source, cur.target_date AS date,
+                prev.target_date AS previous_date, prev.run_id
+                FROM rows cur JOIN rows prev
+                ON prev.symbol = cur.symbol AND prev.source = cur.source
+                AND prev.run_id < cur.run_id
+                AND abs(julianday(prev.target_date)
+                    - julianday(cur.target_date)) <= ?3
+                JOIN runs prev_run
+                ON prev_run.id = prev.run_id AND prev_run.replay = 0
+                WHERE cur.run_id = ?1 AND cur.target_date = ?2
+            ), latest AS (
+                SELECT * FROM previous p
+                WHERE p.run_id = (
+                    SELECT max(run_id) FROM previous
+                    WHERE symbol = p.symbol AND source = p.source
+                )
+            )
+            SELECT DISTINCT latest.symbol, latest.source,
+            latest.previous_date, latest.date, runs.started
+            FROM latest JOIN runs ON runs.id = latest.run_id
+            WHERE NOT EXISTS (
+                SELECT 1 FROM latest same
+                WHERE same.symbol = latest.symbol
+                AND same.source = latest.source
+                AND same.previous_date = latest.date
+            ) AND NOT EXISTS (
+                SELECT 1 FROM rows cur
+                WHERE cur.run_id = ?1 AND cur.symbol = latest.symbol
+                AND cur.source = latest.source
+                AND cur.target_date = latest.previous_date
+            )
+            ORDER BY latest.symbol, latest.source, latest.previous_date",
+        )?;
+        let changes = statement
+            .query_map(params![run_id, date, MAX_DATE_CHANGE_DAYS], |row| {
+                Ok(DateChange {
+                    symbol: row.get(0)?,
+                    source: row.get(1)?,
+                    previous_date: row.get(2)?,
+                    date: row.get(3)?,
+                    previously_listed: row.get(4)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(changes)
+    }
+
+    /// Returns every listing of the normalized `symbol` recorded by
+    /// runs started since the given time, oldest first.
+    pub fn symbol_history(
+        &self,
+        symbol: &str,
+        since: DateTime<Local>,
+    ) -> anyhow::Result<Vec<SymbolListing>> {
+        let mut statement = self.conn.prepare(
+            "SELECT runs.started, rows.source, rows.target_date, rows.timing
+            FROM rows JOIN runs ON runs.id = rows.run_id
+            WHERE rows.symbol = ?1 AND runs.replay = 0
+            AND julianday(runs.started) >= julianday(?2)
+            ORDER BY runs.id, rows.target_date, rows.source",
+        )?;
+        let listings = statement
+            .query_map(params![symbol, since], |row| {
+                Ok(SymbolListing {
+                    run_started: row.get(0)?,
+                    source: row.get(1)?,
+                    target_date: row.get(2)?,
+                    timing: row.get(3)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(listings)
+    }
+}
+
+#[test]
+fn record_and_query_symbol() {
+    use crate::candidate::{eval_candidates, event};
+
+    let mut history = History::open(Path::new(":memory:")).unwrap();
+    let date = NaiveDate::from_ymd_opt(2024, 5, 22).unwrap();
+    let day = DayData {
+        date,
+        events: vec![
+            event("Zacks", "NVDA", ""),
+            event("TradingView", "NASDAQ:NVDA", ""),
+        ],
+        attempted: vec!["Zacks", "TradingView", "Benzinga"],
+        succeeded: vec!["Zacks", "TradingView"],
+    };
+    let normalizer = SymbolNormalizer::default();
+    let started = Local::now();
+    let run_id = history.start_run(started, false, 1).unwrap();
+    history.record_day(run_id, &day, &normalizer).unwrap();
+    let candidates = DayCandidates {
+        date,
+        attempted: day.attempted.clone(),
+        succeeded: day.succeeded.clone(),
+        candidates: eval_candidates(day.events, 1, &normalizer),
+        date_changes: Vec::new(),
+        date_conflicts: Vec::new(),
+    };
+    history.record_candidates(run_id, &candidates).unwrap();
+
+    let listings = history.symbol_history("NVDA", started).unwrap();
+    let sources: Vec<&str> =
+        listings.iter().map(|l| l.source.as_str()).collect();
+    assert_eq!(sources, ["TradingView", "Zacks"]);
+    assert!(listings.iter().all(|l| l.target_date == date));
+
+    let later = started + chrono::Duration::seconds(1);
+    assert!(history.symbol_history("NVDA", later).unwrap().is_empty());
+
+    let previous = history.previous_run(date, None).unwrap().unwrap();
+    assert_eq!(previous.min_refs, 1);
+    assert_eq!(
+        previous.candidates,
+        [RecordedCandidate {
+            symbol: "NVDA".to_owned(),
+            refs: 2
+        }]
+    );
+    assert!(history.previous_run(date, Some(run_id)).unwrap().is_none());
+
+    // Runs replaying snapshots aren't diffed against.
+    let replay_id = history.start_run(Local::now(), true, 3).unwrap();
+    let day = DayData {
+        events: Vec::new(),
+        ..day
+    };
+    history.record_day(replay_id, &day, &normalizer).unwrap();
+    let previous = history.previous_run(date, None).unwrap().unwrap();
+    assert_eq!(previous.min_refs, 1);
+}
+
+#[test]
+fn learn_agreement_rates() {
+    use crate::candidate::event;
+
+    let mut history = History::open(Path::new(":memory:")).unwrap();
+    let day = DayData {
+        date: NaiveDate::from_ymd_opt(2024, 5, 22).unwrap(),
+        events: vec![
+            event("Zacks", "NVDA", ""),
+            event("Benzinga", "NVDA", ""),
+            event("Benzinga", "IBKR", ""),
+            event("Benzinga", "LOW", ""),
+            event("Benzinga", "WMT", ""),
+        ],
+        attempted: vec!["Zacks", "Benzinga"],
+        succeeded: vec!["Zacks", "Benzinga"],
+    };
+    let run_id = history.start_run(Local::now(), false, 1).unwrap();
+    history
+        .record_day(run_id, &day, &SymbolNormalizer::default())
+        .unwrap();
+
+    let rates = history.agreement_rates().unwrap();
+    assert_eq!(rates.len(), 2);
+    assert_eq!(rates["Zacks"], 1.0);
+    assert_eq!(rates["Benzinga"], 0.25);
+}
+
+#[test]
+fn reconcile_predictions() {
+    use crate::{candidate::event, parser::EarningsEvent};
+    use chrono::TimeZone;
+
+    let mut history = History::open(Path::new(":memory:")).unwrap();
+    let normalizer = SymbolNormalizer::default();
+    let date = |day| NaiveDate::from_ymd_opt(2024, 5, day).unwrap();
+    // Records the listings of the 22nd in a run started on the `day`.
+    let record_run =
+        |history: &mut History,
+         day,
+         listings: &[(&'static str, &str, bool)]| {
+            let started =
+                Local.with_ymd_and_hms(2024, 5, day, 12, 0, 0).unwrap();
+            let run_id = history.start_run(started, false, 1).unwrap();
+            let events = listings
+                .iter()
+                .map(|&(source, symbol, reported)| EarningsEvent {
+                    eps_reported: reported.then(|| "1.00".to_owned()),
+                    ..event(source, symbol, "")
+                })
+                .collect();
+            let day = DayData {
+                date: date(22),
+                events,
+                attempted: Vec::new(),
+                succeeded: Vec::new(),
+            };
+            history.record_day(run_id, &day, &normalizer).unwrap();
+        };
+
+    record_run(
+        &mut history,
+        20,
+        &[
+            ("Zacks", "NVDA", false),
+            ("Zacks", "WMT", false),
+            ("Benzinga", "NVDA", false),
+        ],
+    );
+    assert_eq!(history.reconcile(date(24)).unwrap(), 0);
+
+    // A run on the date only lists the EPS reported before the open.
+    record_run(&mut history, 22, &[("Zacks", "WMT", true)]);
+    assert_eq!(history.reconcile(date(24)).unwrap(), 0);
+
+    // The run after the date lists the reported EPS.
+    record_run(
+        &mut history,
+        23,
+        &[("Zacks", "NVDA", true), ("Investing", "TGT", true)],
+    );
+    assert_eq!(history.reconcile(date(22)).unwrap(), 0);
+    assert_eq!(history.reconcile(date(24)).unwrap(), 1);
+
+    let stats = history.source_stats(date(1)).unwrap();
+    assert_eq!(
+        stats,
+        [
+            SourceStats {
+                source: "Benzinga".to_owned(),
+                predicted: 1,
+                confirmed: 1,
+                reported: 2,
+            },
+            SourceStats {
+                source: "Zacks".to_owned(),
+                predicted: 2,
+                confirmed: 1,
+                reported: 2,
+            },
+        ]
+    );
+    assert_eq!(stats[1].precision(), 0.5);
+    assert_eq!(stats[1].recall(), 0.5);
+    assert!(history.source_stats(date(23)).unwrap().is_empty());
+}
+
+#[test]
+fn reopen_keeps_schema() {
+    let dir = std::env::temp_dir()
+        .join(format!("earnings-helper-history-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(DEFAULT_HISTORY);
+    let history = History::open(&path).unwrap();
+    history.start_run(Local::now(), true, 2).unwrap();
+    drop(history);
+
+    let history = History::open(&path).unwrap();
+    let runs: i64 = history
+        .conn
+        .query_row("SELECT count(*) FROM runs", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(runs, 1);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn detect_date_changes() {
+    use crate::{candidate::event, parser::EarningsEvent};
+
+    let mut history = History::open(Path::new(":memory:")).unwrap();
+    let normalizer = SymbolNormalizer::default();
+    let date = |day| NaiveDate::from_ymd_opt(2024, 5, day).unwrap();
+    let mut record_run = |replay, listings: &[(&'static str, &str, u32)]| {
+        let run_id = history.start_run(Local::now(), replay, 1).unwrap();
+        for day in 1..=31 {
+            let events: Vec<EarningsEvent> = listings
+                .iter()
+                .filter(|(_, _, d)| *d == day)
+                .map(|&(source, symbol, _)| event(source, symbol, ""))
+                .collect();
+            let day = DayData {
+                date: date(day),
+                events,
+                attempted: vec!["Zacks", "Benzinga"],
+                succeeded: vec!["Zacks", "Benzinga"],
+            };
+            history.record_day(run_id, &day, &normalizer).unwrap();
+        }
+        run_id
+    };
+
+    record_run(false, &[("Zacks", "NVDA", 22), ("Benzinga", "NVDA", 22)]);
+    record_run(false, &[("Zacks", "NVDA", 23), ("Benzinga", "NVDA", 22)]);
+    // Replayed old pages aren't earlier listings.
+    record_run(true, &[("Zacks", "WMT", 15), ("Benzinga", "NVDA", 21)]);
+    let run_id = record_run(
+        false,
+        &[
+            ("Zacks", "NVDA", 23),
+            ("Benzinga", "NVDA", 23),
+            ("Zacks", "WMT", 16),
+            ("Benzinga", "WMT", 16),
+        ],
+    );
+
+    let changes = history.date_changes(run_id, date(23)).unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].symbol, "NVDA");
+    assert_eq!(changes[0].source, "Benzinga");
+    assert_eq!(changes[0].previous_date, date(22));
+    assert_eq!(changes[0].date, date(23));
+    assert!(history.date_changes(run_id, date(16)).unwrap().is_empty());
+}
+
+#[test]
+fn skip_replay_runs() {
+    use crate::{candidate::event, parser::EarningsEvent};
+    use chrono::TimeZone;
+
+    let mut history = History::open(Path::new(":memory:")).unwrap();
+    let normalizer = SymbolNormalizer::default();
+    let date = NaiveDate::from_ymd_opt(2024, 5, 22).unwrap();
+    // Records the listings of the 22nd in a run started on the `day`.
+    let mut record_run =
+        |day, replay, listings: &[(&'static str, &str, bool)]| {
+            let started =
+                Local.with_ymd_and_hms(2024, 5, day, 12, 0, 0).unwrap();
+            let run_id = history.start_run(started, replay, 1).unwrap();
+            let events = listings
+                .iter()
+                .map(|&(source, symbol, reported)| EarningsEvent {
+                    eps_reported: reported.then(|| "1.00".to_owned()),
+                    ..event(source, symbol, "")
+                })
+                .collect();
+            let day = DayData {
+                date,
+                events,
+                attempted: vec!["Zacks", "Benzinga"],
+                succeeded: vec!["Zacks", "Benzinga"],
+            };
+            history.record_day(run_id, &day, &normalizer).unwrap();
+        };
+
+    record_run(
+        20,
+        false,
+        &[
+            ("Zacks", "NVDA", false),
+            ("Zacks", "WMT", false),
+            ("Benzinga", "NVDA", false),
+        ],
+    );
+    record_run(23, false, &[("Zacks", "NVDA", true)]);
+    // Old pages replayed later list other symbols.
+    record_run(24, true, &[("Benzinga", "LOW", false)]);
+    record_run(25, true, &[("Zacks", "WMT", true), ("Zacks", "LOW", true)]);
+
+    let today = NaiveDate::from_ymd_opt(2024, 5, 26).unwrap();
+    assert_eq!(history.reconcile(today).unwrap(), 1);
+    let stats = history.source_stats(date).unwrap();
+    assert_eq!(
+        stats,
+        [
+            SourceStats {
+                source: "Benzinga".to_owned(),
+                predicted: 1,
+                confirmed: 1,
+                reported: 1,
+            },
+            SourceStats {
+                source: "Zacks".to_owned(),
+                predicted: 2,
+                confirmed: 1,
+                reported: 1,
+            },
+        ]
+    );
+    let rates = history.agreement_rates().unwrap();
+    assert_eq!(rates["Benzinga"], 1.0);
+    assert_eq!(rates["Zacks"], 1.0 / 3.0);
+    let previous = history.previous_run(date, None).unwrap().unwrap();
+    assert_eq!(
+        previous.started,
+        Local.with_ymd_and_hms(2024, 5, 23, 12, 0, 0).unwrap()
+    );
+    let since = Local.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap();
+    assert!(history.symbol_history("LOW", since).unwrap().is_empty());
+    assert_eq!(history.symbol_history("NVDA", since).unwrap().len(), 3);
+}
@@ -1,146 +1,502 @@
+mod candidate;
 mod commands;
+mod config;
+mod conflict;
+mod diff;
+mod history;
+mod output;
 mod parser;
+mod score;
+mod symbol;
 
-use crate::parser::RelativeDay;
-use chrono::{Days, Local};
-use parser::Company;
-use std::io::Write;
-
-const OUTPUT_FILE_NAME: &str = "company_candidates.txt";
-
-#[derive(Debug, Ord, PartialEq, Eq, PartialOrd)]
-struct CompanyCandidate {
-    company: Company,
-    refs: usize,
-}
+use crate::{
+    candidate::{eval_candidates, DayCandidates},
+    config::{Config, WebDriverConfig},
+    diff::CandidateDiff,
+    history::History,
+    output::Format,
+    parser::{Browser, Capture, DayData, LocalDriver},
+    score::SourceWeights,
+    symbol::SymbolNormalizer,
+};
+use chrono::{Datelike, Days, Local, NaiveDate};
+use std::{io::Write, path::PathBuf};
 
 #[tokio::main]
 async fn main() {
+    let started = Local::now();
     let mut stdout = std::io::stdout().lock();
+    // The progress is kept apart from the output, which can be piped
+    // with --stdout.
+    let mut stderr = std::io::stderr().lock();
     let matches = commands::cmd().get_matches();
+    let mut config = match matches.get_one::<PathBuf>("config") {
+        Some(path) => Config::load(path).unwrap(),
+        None => Config::default(),
+    };
+    if let Some(browser) = matches.get_one::<String>("browser") {
+        config.webdriver.browser = Browser::from_name(browser).unwrap();
+    }
+    let normalizer = SymbolNormalizer::new(&config.aliases);
+    let history_path = matches.get_one::<PathBuf>("history").unwrap();
 
-    write!(stdout, "Data parsing in progress for: ").unwrap();
+    if let Some(("history", sub_matches)) = matches.subcommand() {
+        let history = History::open(history_path).unwrap();
+        let symbol = sub_matches.get_one::<String>("symbol").unwrap();
+        let days = *sub_matches.get_one::<u64>("days").unwrap();
+        let since = started.checked_sub_days(Days::new(days)).unwrap();
+        print_symbol_history(&mut stdout, &history, &normalizer, symbol, since)
+            .unwrap();
+        return;
+    }
+    if let Some(("stats", sub_matches)) = matches.subcommand() {
+        let history = History::open(history_path).unwrap();
+        let days = *sub_matches.get_one::<u64>("days").unwrap();
+        let today = started.date_naive();
+        let since = today.checked_sub_days(Days::new(days)).unwrap();
+        history.reconcile(today).unwrap();
+        print_stats(&mut stdout, &history, since).unwrap();
+        return;
+    }
+    let mut history = (!matches.get_flag("no-history"))
+        .then(|| History::open(history_path).unwrap());
+    let mut weights = SourceWeights::new(&config.weights);
+    if let (Some(history), true) =
+        (history.as_ref(), matches.get_flag("learn-weights"))
+    {
+        history.reconcile(started.date_naive()).unwrap();
+        let stats = history.source_stats(NaiveDate::MIN).unwrap();
+        weights.learn(history.agreement_rates().unwrap(), &stats);
+    }
+
+    write!(stderr, "Data parsing in progress for: ").unwrap();
 
-    let date = Local::now();
-    let day = if matches.get_flag("tmr") {
-        let tomorrow_date = date.checked_add_days(Days::new(1)).unwrap().date_naive();
-        write!(stdout, "TOMORROW ({})", tomorrow_date).unwrap();
-        RelativeDay::Tomorrow
+    let today = Local::now().date_naive();
+    let mut dates = if let Some(date) = matches.get_one::<NaiveDate>("date") {
+        write!(stderr, "{}", date).unwrap();
+        vec![*date]
+    } else if let (Some(from), Some(to)) = (
+        matches.get_one::<NaiveDate>("from"),
+        matches.get_one::<NaiveDate>("to"),
+    ) {
+        if from > to {
+            writeln!(stderr, "\nThe range start is after the range end.")
+                .unwrap();
+            std::process::exit(1);
+        }
+        write!(stderr, "{} - {}", from, to).unwrap();
+        from.iter_days().take_while(|date| date <= to).collect()
+    } else if matches.get_flag("tmr") {
+        let tomorrow_date = today.checked_add_days(Days::new(1)).unwrap();
+        write!(stderr, "TOMORROW ({})", tomorrow_date).unwrap();
+        vec![tomorrow_date]
     } else if matches.get_flag("yda") {
-        let yesterday_date = date.checked_sub_days(Days::new(1)).unwrap().date_naive();
-        write!(stdout, "YESTERDAY ({})", yesterday_date).unwrap();
-        RelativeDay::Yesterday
+        let yesterday_date = today.checked_sub_days(Days::new(1)).unwrap();
+        write!(stderr, "YESTERDAY ({})", yesterday_date).unwrap();
+        vec![yesterday_date]
     } else {
-        write!(stdout, "TODAY ({})", date.date_naive()).unwrap();
-        RelativeDay::Today
+        write!(stderr, "TODAY ({})", today).unwrap();
+        vec![today]
     };
+    let whole_week = matches.get_flag("week");
+    if whole_week {
+        dates = week_days(dates[0]);
+        write!(
+            stderr,
+            "\nWhole week: {} - {}",
+            dates[0],
+            dates[dates.len() - 1]
+        )
+        .unwrap();
+    }
     let window_visibility = matches.get_flag("preview");
 
     let min_references = *matches.get_one::<u8>("refs").unwrap() as usize;
-    write!(stdout, "\nMinimum references: {min_references}\n").unwrap();
+    let min_score = *matches.get_one::<f64>("min-score").unwrap();
+    write!(stderr, "\nMinimum references: {min_references}").unwrap();
+    write!(stderr, "\nMinimum score: {min_score}\n").unwrap();
 
-    let (data, parsed_websites) =
-        parser::parse_website_data(day, window_visibility)
-            .await
+    let format = matches.get_one::<String>("format").unwrap();
+    let format = Format::from_name(format).unwrap();
+    let overwrite = !matches.get_flag("no-clobber");
+    let output_path = (!matches.get_flag("stdout")).then(|| {
+        let template = matches.get_one::<String>("output").unwrap();
+        output::output_path(template, &dates, min_references, format, started)
+    });
+    if let Some(path) = output_path.as_ref() {
+        if !overwrite && path.exists() {
+            writeln!(
+                stderr,
+                "The output file '{}' already exists.",
+                path.display()
+            )
             .unwrap();
+            std::process::exit(1);
+        }
+    }
 
-    write!(
-        stdout,
-        "\nSuccessfully parsed websites: {} out of 5",
-        parsed_websites
-    )
-    .unwrap();
-    if data.is_empty() {
-        write!(
-            stdout,
-            "\nTotal number of entries: 0",
+    let replay_dir = matches.get_one::<PathBuf>("replay");
+    let mut days = if let Some(dir) = replay_dir {
+        writeln!(stderr, "Replaying saved pages from '{}'", dir.display())
+            .unwrap();
+        parser::replay_snapshots(dir, &dates).unwrap()
+    } else {
+        let capture = matches.get_one::<PathBuf>("capture").map(|root| {
+            Capture::new(root, matches.get_flag("screenshots")).unwrap()
+        });
+        let jobs = *matches.get_one::<u8>("jobs").unwrap() as usize;
+        let no_browser = matches.get_flag("no-browser");
+        let local_drivers = if matches.get_flag("start-driver") {
+            match start_drivers(&mut stderr, &config.webdriver, jobs).await {
+                Ok(drivers) => drivers,
+                Err(e) => {
+                    writeln!(stderr, "\nError: {e:#}").unwrap();
+                    std::process::exit(1);
+                }
+            }
+        } else {
+            Vec::new()
+        };
+        let endpoints: Vec<String> = if no_browser {
+            Vec::new()
+        } else if local_drivers.is_empty() {
+            let browser = config.webdriver.browser;
+            let url = config.webdriver.url.as_deref();
+            vec![url.unwrap_or(browser.default_url()).to_owned(); jobs]
+        } else {
+            local_drivers
+                .iter()
+                .map(LocalDriver::url)
+                .cycle()
+                .take(jobs)
+                .collect()
+        };
+        let caps = parser::capabilities(&config.webdriver, !window_visibility)
+            .unwrap();
+        let client = parser::http_client(&config.webdriver).unwrap();
+        let parsing = parser::parse_website_data(
+            &dates, whole_week, &endpoints, caps, client, capture,
+        );
+        let days = tokio::select! {
+            days = parsing => Some(days),
+            _ = tokio::signal::ctrl_c() => None,
+        };
+        for local_driver in local_drivers {
+            if let Err(e) = local_driver.stop().await {
+                let name = config.webdriver.browser.driver_name();
+                writeln!(stderr, "\nWarning: Couldn't stop {name}: {e:#}")
+                    .unwrap();
+            }
+        }
+        match days {
+            Some(Ok(days)) => days,
+            Some(Err(e)) => {
+                writeln!(stderr, "\nError: {e:#}").unwrap();
+                std::process::exit(1);
+            }
+            None => {
+                writeln!(stderr, "\nInterrupted.").unwrap();
+                std::process::exit(130);
+            }
+        }
+    };
+
+    let run_id = history.as_ref().map(|history| {
+        history
+            .start_run(started, replay_dir.is_some(), min_references)
+            .unwrap()
+    });
+    if let (Some(history), Some(run_id)) = (history.as_mut(), run_id) {
+        for day in days.iter() {
+            history.record_day(run_id, day, &normalizer).unwrap();
+        }
+        // Reported EPS of the past dates confirm the earlier listings.
+        history.reconcile(started.date_naive()).unwrap();
+    }
+    // The listings are recorded on the dates listed by the websites,
+    // but evaluated on the date most of them agree on.
+    let mut conflicts = conflict::resolve_conflicts(&mut days, &normalizer);
+
+    let mut candidates_by_date = Vec::with_capacity(days.len());
+    for day in days {
+        let date = day.date;
+        let mut candidates = eval_day(
+            &mut stderr,
+            day,
+            min_references,
+            min_score,
+            &normalizer,
+            &weights,
         )
         .unwrap();
-        std::process::exit(0);
-    };
-    let avg = parsed_websites / data.len();
-    write!(
-        stdout,
-        "\nTotal number of entries (no filter): {}",
-        data.len()
-    )
-    .unwrap();
+        (candidates.date_conflicts, conflicts) =
+            conflicts.into_iter().partition(|c| c.chosen == date);
+        for conflict in candidates.date_conflicts.iter() {
+            write!(stderr, "\nWarning: {}", output::date_conflict(conflict))
+                .unwrap();
+        }
+        if let (Some(history), Some(run_id)) = (history.as_mut(), run_id) {
+            candidates.date_changes =
+                history.date_changes(run_id, date).unwrap();
+            for change in candidates.date_changes.iter() {
+                write!(stderr, "\nWarning: {}", output::date_change(change))
+                    .unwrap();
+            }
+            if matches.get_flag("diff") {
+                let min_refs = min_references;
+                print_diff(&mut stderr, history, run_id, &candidates, min_refs)
+                    .unwrap();
+            }
+            history.record_candidates(run_id, &candidates).unwrap();
+        }
+        candidates_by_date.push(candidates);
+    }
+    writeln!(stderr).unwrap();
+    stderr.flush().unwrap();
 
-    write!(
-        stdout,
-        "\nEntries with less than {} references will be filtered.",
-        min_references
-    )
-    .unwrap();
-    write!(stdout, "\nEvaluating parsed companies...").unwrap();
-    stdout.flush().unwrap();
-    let mut candidates = eval_candidates(data, min_references, avg);
-    candidates.sort_by(|a, b| a.refs.cmp(&b.refs).reverse());
-    write!(stdout, " Done!").unwrap();
-    write!(
-        stdout,
-        "\nNumber of entries after filtering: {}\n",
-        candidates.len()
-    )
-    .unwrap();
-    std::io::stdout().flush().unwrap();
+    let output =
+        output::data_output(&candidates_by_date, min_references, format)
+            .unwrap();
+    match output_path {
+        Some(path) => {
+            let path =
+                output::data_file_output(&path, &output, overwrite).unwrap();
+            println!("Parsed data saved at:\n\t'{}'", path.display());
+        }
+        None => {
+            print!("{output}");
+            if !output.ends_with('\n') {
+                println!();
+            }
+        }
+    }
+}
 
-    data_file_output(candidates).unwrap();
+/// Starts the WebDriver processes of the configured browser for
+/// `jobs` sessions.
+async fn start_drivers(
+    stderr: &mut impl Write,
+    config: &WebDriverConfig,
+    jobs: usize,
+) -> anyhow::Result<Vec<LocalDriver>> {
+    let browser = config.browser;
+    // Geckodriver only runs one session, so each session gets its own.
+    let count = if browser.single_session() { jobs } else { 1 };
+    write!(stderr, "Starting {}...", browser.driver_name())?;
+    stderr.flush()?;
+    let mut drivers = Vec::with_capacity(count);
+    for _ in 0..count {
+        drivers.push(LocalDriver::start(browser, config.driver_path()).await?);
+    }
+    let urls: Vec<String> = drivers.iter().map(LocalDriver::url).collect();
+    writeln!(stderr, " Listening at {}", urls.join(", "))?;
+    Ok(drivers)
 }
 
-/// Evaluate candidates by data corelation. If the parsed company has
-/// multiple duplicates (references) it will be shown if it's more than
-/// the MINIMUM_REFERENCES.
-///
-/// `avg` - represents average number of companies parsed per website,
-/// needed for allocating space for [`Vec::with_capacity()`]
-fn eval_candidates(
-    mut data: Vec<Company>,
+/// Prints the changes of the candidates since the latest recorded run,
+/// other than the current one, for the same date.
+fn print_diff(
+    stderr: &mut impl Write,
+    history: &History,
+    run_id: i64,
+    day: &DayCandidates,
     min_refs: usize,
-    avg: usize,
-) -> Vec<CompanyCandidate> {
-    let mut result = Vec::with_capacity(avg);
-    loop {
-        let mut references: usize = 1;
-        let mut company = data.swap_remove(0);
-        loop {
-            if let Some(i) = data.iter().position(|c| c.eq(&company)) {
-                references += 1;
-                let dup = data.swap_remove(i);
-                // If variable's 'company' name is empty insert duplicate's name.
-                if company.name.is_empty() {
-                    company.name = dup.name;
-                }
-                continue;
-            }
-            break;
+) -> anyhow::Result<()> {
+    let Some(previous) = history.previous_run(day.date, Some(run_id))? else {
+        write!(stderr, "\nNo previous run to compare with.")?;
+        return Ok(());
+    };
+    write!(
+        stderr,
+        "\nChanges since the run from {}:",
+        previous.started.format("%Y-%m-%d %H:%M:%S")
+    )?;
+    if previous.min_refs != min_refs {
+        write!(
+            stderr,
+            "\n(The previous run used {} minimum references.)",
+            previous.min_refs
+        )?;
+    }
+    let diff = CandidateDiff::new(&previous.candidates, &day.candidates);
+    if diff.is_empty() {
+        write!(stderr, "\nNo changes.")?;
+    }
+    if !diff.added.is_empty() {
+        write!(stderr, "\nAdded:")?;
+        for (symbol, refs) in diff.added {
+            write!(stderr, "\n\t+ {symbol:<7}\t{refs} refs")?;
         }
-        if references >= min_refs {
-            result.push(CompanyCandidate {
-                company,
-                refs: references,
-            })
+    }
+    if !diff.removed.is_empty() {
+        write!(stderr, "\nRemoved:")?;
+        for (symbol, refs) in diff.removed {
+            write!(stderr, "\n\t- {symbol:<7}\t{refs} refs")?;
         }
-        if data.is_empty() {
-            break;
+    }
+    if !diff.changed.is_empty() {
+        write!(stderr, "\nChanged:")?;
+        for (symbol, previous, current) in diff.changed {
+            write!(stderr, "\n\t~ {symbol:<7}\t{previous} -> {current} refs")?;
         }
     }
-    result
+    Ok(())
 }
 
-fn data_file_output(data: Vec<CompanyCandidate>) -> anyhow::Result<()> {
-    let mut output = String::new();
-    output.push_str("Refs.\tSymbol \tCompany Name\n");
-    for d in data.into_iter() {
-        output.push_str(&format!(
-            "{:>5}\t{:<7}\t{}\n",
-            d.refs, d.company.symbol, d.company.name
-        ));
-    }
-    std::fs::write(OUTPUT_FILE_NAME, output).unwrap();
-    println!(
-        "Parsed data saved at:\n\t'{}'",
-        std::fs::canonicalize(OUTPUT_FILE_NAME)?.display()
-    );
+/// Prints what each website listed for the `symbol` in the runs
+/// recorded since the given time.
+fn print_symbol_history(
+    stdout: &mut impl Write,
+    history: &History,
+    normalizer: &SymbolNormalizer,
+    symbol: &str,
+    since: chrono::DateTime<Local>,
+) -> anyhow::Result<()> {
+    let symbol = normalizer.normalize(symbol);
+    let listings = history.symbol_history(&symbol, since)?;
+    writeln!(stdout, "History of {symbol} since {}:", since.date_naive())?;
+    if listings.is_empty() {
+        writeln!(stdout, "No recorded listings.")?;
+        return Ok(());
+    }
+    writeln!(
+        stdout,
+        "Run                \tSource      \tDate      \tTime"
+    )?;
+    for listing in listings {
+        writeln!(
+            stdout,
+            "{}\t{:<12}\t{}\t{}",
+            listing.run_started.format("%Y-%m-%d %H:%M:%S"),
+            listing.source,
+            listing.target_date,
+            listing.timing
+        )?;
+    }
+    Ok(())
+}
+
+/// Prints the precision and recall of each website on the earnings
+/// dates since the `since` date.
+fn print_stats(
+    stdout: &mut impl Write,
+    history: &History,
+    since: NaiveDate,
+) -> anyhow::Result<()> {
+    let stats = history.source_stats(since)?;
+    writeln!(stdout, "Confirmed listings since {since}:")?;
+    if stats.is_empty() {
+        writeln!(
+            stdout,
+            "No reconciled dates. Parse past dates to record \
+            the reported earnings."
+        )?;
+        return Ok(());
+    }
+    writeln!(stdout, "Source      \tListed\tConfirmed\tPrecision\tRecall")?;
+    for s in stats {
+        writeln!(
+            stdout,
+            "{:<12}\t{:>6}\t{:>9}\t{:>8.0}%\t{:>5.0}%",
+            s.source,
+            s.predicted,
+            s.confirmed,
+            s.precision() * 100.0,
+            s.recall() * 100.0
+        )?;
+    }
     Ok(())
 }
+
+/// Returns the trading days (Mon to Fri) of the week containing `date`.
+fn week_days(date: NaiveDate) -> Vec<NaiveDate> {
+    let weekday_num = date.weekday().num_days_from_monday() as u64;
+    let monday = date.checked_sub_days(Days::new(weekday_num)).unwrap();
+    monday.iter_days().take(5).collect()
+}
+
+/// Evaluates the parsed data of a single date and reports
+/// the progress.
+fn eval_day(
+    stderr: &mut impl Write,
+    day: DayData,
+    min_references: usize,
+    min_score: f64,
+    normalizer: &SymbolNormalizer,
+    weights: &SourceWeights,
+) -> anyhow::Result<DayCandidates> {
+    let DayData {
+        date,
+        events: data,
+        attempted,
+        succeeded,
+    } = day;
+    write!(stderr, "\n\n[{}]", date)?;
+    write!(
+        stderr,
+        "\nSuccessfully parsed websites: {} out of {}",
+        succeeded.len(),
+        attempted.len()
+    )?;
+    let mut result = DayCandidates {
+        date,
+        attempted,
+        succeeded,
+        candidates: Vec::new(),
+        date_changes: Vec::new(),
+        date_conflicts: Vec::new(),
+    };
+    if data.is_empty() {
+        write!(stderr, "\nTotal number of entries: 0")?;
+        return Ok(result);
+    };
+    write!(
+        stderr,
+        "\nTotal number of entries (no filter): {}",
+        data.len()
+    )?;
+
+    write!(
+        stderr,
+        "\nEntries with less than {} references or a score below {} \
+        will be filtered.",
+        min_references, min_score
+    )?;
+    write!(stderr, "\nEvaluating parsed companies...")?;
+    stderr.flush()?;
+    let mut candidates = eval_candidates(data, min_references, normalizer);
+    score::score_candidates(
+        &mut candidates,
+        weights,
+        &result.succeeded,
+        min_score,
+    );
+    write!(stderr, " Done!")?;
+    for candidate in candidates.iter() {
+        let merged = candidate.merged_symbols();
+        if !merged.is_empty() {
+            write!(
+                stderr,
+                "\nMerged symbols: {} <- {}",
+                candidate.event.company.symbol,
+                merged.join(", ")
+            )?;
+        }
+    }
+    write!(
+        stderr,
+        "\nNumber of entries after filtering: {}",
+        candidates.len()
+    )?;
+    result.candidates = candidates;
+    Ok(result)
+}
+
+#[test]
+fn trading_days_of_week() {
+    let date = NaiveDate::from_ymd_opt(2024, 6, 9).unwrap();
+    let days = week_days(date);
+    assert_eq!(days.len(), 5);
+    assert_eq!(days[0], NaiveDate::from_ymd_opt(2024, 6, 3).unwrap());
+    assert_eq!(days[4], NaiveDate::from_ymd_opt(2024, 6, 7).unwrap());
+}
@@ -0,0 +1,325 @@
+use chrono::{DateTime, Datelike, Days, NaiveDate, NaiveTime, Utc};
+
+use crate::{
+    candidate::{CompanyCandidate, DayCandidates},
+    parser::ReportTiming,
+};
+
+const TIME_ZONE: &str = "America/New_York";
+/// Time zone definition of US exchanges, with the daylight saving
+/// time rules in effect since 2007.
+const VTIMEZONE: [&str; 17] = [
+    "BEGIN:VTIMEZONE",
+    "TZID:America/New_York",
+    "BEGIN:DAYLIGHT",
+    "TZOFFSETFROM:-0500",
+    "TZOFFSETTO:-0400",
+    "TZNAME:EDT",
+    "DTSTART:20070311T020000",
+    "RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=2SU",
+    "END:DAYLIGHT",
+    "BEGIN:STANDARD",
+    "TZOFFSETFROM:-0400",
+    "TZOFFSETTO:-0500",
+    "TZNAME:EST",
+    "DTSTART:20071104T020000",
+    "RRULE:FREQ=YEARLY;BYMONTH=11;BYDAY=1SU",
+    "END:STANDARD",
+    "END:VTIMEZONE",
+];
+/// Maximum length of a content line in octets, without the line break.
+const MAX_LINE_LENGTH: usize = 75;
+/// Sequence numbers of the events count the seconds since
+/// 2020-01-01 00:00 UTC.
+const SEQUENCE_EPOCH: i64 = 1_577_836_800;
+
+/// Writes an iCalendar (RFC 5545) document with an event for every
+/// candidate. Events with a known report timing are placed around
+/// the market open or close, the others last the whole day.
+///
+/// UIDs are made of the symbol and the fiscal period of the report, so
+/// importing the output of a rerun updates the already imported events,
+/// even when the report moved to another date. Reports without a known
+/// fiscal period fall back to their date and get a new UID when they
+/// move. The sequence number grows with the `stamp`, so every export
+/// revises the earlier ones.
+pub(super) fn ics_output(
+    days: &[DayCandidates],
+    stamp: DateTime<Utc>,
+) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_owned(),
+        "VERSION:2.0".to_owned(),
+        "PRODID:-//earnings-helper//Earnings Calendar//EN".to_owned(),
+        "CALSCALE:GREGORIAN".to_owned(),
+        "METHOD:PUBLISH".to_owned(),
+    ];
+    lines.extend(VTIMEZONE.iter().map(|line| line.to_string()));
+    for day in days {
+        for candidate in day.candidates.iter() {
+            lines.extend(event_lines(day.date, candidate, stamp));
+        }
+    }
+    lines.push("END:VCALENDAR".to_owned());
+
+    let mut output = String::new();
+    for line in lines {
+        output.push_str(&fold(&line));
+        output.push_str("\r\n");
+    }
+    output
+}
+
+fn event_lines(
+    date: NaiveDate,
+    candidate: &CompanyCandidate,
+    stamp: DateTime<Utc>,
+) -> Vec<String> {
+    let event = &candidate.event;
+    let symbol = &event.company.symbol;
+    let period = event.fiscal_period.as_deref().and_then(fiscal_quarter);
+    let period = period.unwrap_or_else(|| date.format("%Y%m%d").to_string());
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_owned(),
+        format!("UID:{period}-{symbol}@earnings-helper"),
+        format!("DTSTAMP:{}", stamp.format("%Y%m%dT%H%M%SZ")),
+        format!("SEQUENCE:{}", stamp.timestamp() - SEQUENCE_EPOCH),
+    ];
+    match time_slot(event.timing) {
+        Some((start, end)) => {
+            let start = date.and_time(start).format("%Y%m%dT%H%M%S");
+            let end = date.and_time(end).format("%Y%m%dT%H%M%S");
+            lines.push(format!("DTSTART;TZID={TIME_ZONE}:{start}"));
+            lines.push(format!("DTEND;TZID={TIME_ZONE}:{end}"));
+        }
+        None => {
+            let start = date.format("%Y%m%d");
+            let end = date.checked_add_days(Days::new(1)).unwrap();
+            let end = end.format("%Y%m%d");
+            lines.push(format!("DTSTART;VALUE=DATE:{start}"));
+            lines.push(format!("DTEND;VALUE=DATE:{end}"));
+        }
+    }
+    let summary = match event.timing {
+        ReportTiming::Unknown => format!("{symbol} earnings"),
+        timing => format!("{symbol} earnings ({})", timing.abbreviation()),
+    };
+    lines.push(format!("SUMMARY:{}", escape(&summary)));
+
+    let mut description = Vec::new();
+    if !event.company.name.is_empty() {
+        description.push(event.company.name.clone());
+    }
+    description.push(format!(
+        "References: {} ({})",
+        candidate.refs,
+        candidate.sources().join(", ")
+    ));
+    description.push(format!("Score: {:.2}", candidate.score));
+    let details = [
+        ("Fiscal period", &event.fiscal_period),
+        ("EPS estimate", &event.eps_estimate),
+        ("Revenue estimate", &event.revenue_estimate),
+        ("Market cap", &event.market_cap),
+    ];
+    for (label, value) in details {
+        if let Some(value) = value {
+            description.push(format!("{label}: {value}"));
+        }
+    }
+    lines.push(format!("DESCRIPTION:{}", escape(&description.join("\n"))));
+    lines.push("TRANSP:TRANSPARENT".to_owned());
+    lines.push("END:VEVENT".to_owned());
+    lines
+}
+
+/// Identifies the reported quarter in the notations of the fiscal period
+/// used by websites. The end date of the period ('2023-03-31' or
+/// '03/31/2023') becomes its calendar quarter ('2023Q1'), a fiscal
+/// quarter ('Q1 2023') is kept as such ('FY2023Q1').
+fn fiscal_quarter(period: &str) -> Option<String> {
+    let period = period.trim();
+    for format in ["%Y-%m-%d", "%m/%d/%Y"] {
+        if let Ok(end) = NaiveDate::parse_from_str(period, format) {
+            return Some(format!("{}Q{}", end.year(), end.month0() / 3 + 1));
+        }
+    }
+    let (quarter, year) = period.strip_prefix('Q')?.split_once(' ')?;
+    let quarter: u32 = quarter.parse().ok()?;
+    let year: i32 = year.trim().parse().ok()?;
+    (1..=4)
+        .contains(&quarter)
+        .then(|| format!("FY{year}Q{quarter}"))
+}
+
+/// Start and end time of the event in the exchange time zone.
+fn time_slot(timing: ReportTiming) -> Option<(NaiveTime, NaiveTime)> {
+    let time = |hour, min| NaiveTime::from_hms_opt(hour, min, 0).unwrap();
+    match timing {
+        ReportTiming::BeforeOpen => Some((time(8, 0), time(9, 30))),
+        ReportTiming::DuringMarket => Some((time(9, 30), time(16, 0))),
+        ReportTiming::AfterClose => Some((time(16, 0), time(17, 30))),
+        ReportTiming::Unknown => None,
+    }
+}
+
+/// Escapes the characters with a special meaning in text values.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Splits the content line into lines of at most [`MAX_LINE_LENGTH`]
+/// octets. Continuation lines start with a space and multi-byte
+/// characters are never split.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > MAX_LINE_LENGTH {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded
+}
+
+#[test]
+fn fold_long_lines() {
+    let line = "D".repeat(160);
+    let folded = fold(&line);
+    let lines: Vec<&str> = folded.split("\r\n").collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0].len(), 75);
+    assert_eq!(lines[1].len(), 75);
+    assert!(lines[1].starts_with(' '));
+    assert_eq!(lines.concat().replace(' ', ""), line);
+
+    // Multi-byte characters stay whole.
+    let folded = fold(&"é".repeat(40));
+    assert!(folded.split("\r\n").all(|line| line.len() <= 75));
+}
+
+#[test]
+fn escape_text_values() {
+    assert_eq!(
+        escape("Netflix, Inc.; Q1\nback\\slash"),
+        "Netflix\\, Inc.\\; Q1\\nback\\\\slash"
+    );
+}
+
+#[test]
+fn events_of_candidates() {
+    use crate::{
+        candidate::{eval_candidates, event},
+        parser::EarningsEvent,
+        symbol::SymbolNormalizer,
+    };
+
+    let event = |symbol, timing| EarningsEvent {
+        timing,
+        ..event("Zacks", symbol, "Company, Inc.")
+    };
+    let data = vec![
+        event("NFLX", ReportTiming::AfterClose),
+        event("IBKR", ReportTiming::Unknown),
+    ];
+    let days = [DayCandidates {
+        date: NaiveDate::from_ymd_opt(2023, 4, 18).unwrap(),
+        attempted: vec!["Zacks"],
+        succeeded: vec!["Zacks"],
+        candidates: eval_candidates(data, 1, &SymbolNormalizer::default()),
+        date_changes: Vec::new(),
+        date_conflicts: Vec::new(),
+    }];
+    let stamp = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+    let output = ics_output(&days, stamp);
+
+    assert!(output.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(output.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+    assert!(output.contains("TZID:America/New_York\r\n"));
+    assert!(output.contains(
+        "BEGIN:VEVENT\r\n\
+        UID:20230418-IBKR@earnings-helper\r\n\
+        DTSTAMP:20231114T221320Z\r\n\
+        SEQUENCE:122163200\r\n\
+        DTSTART;VALUE=DATE:20230418\r\n\
+        DTEND;VALUE=DATE:20230419\r\n\
+        SUMMARY:IBKR earnings\r\n\
+        DESCRIPTION:Company\\, Inc.\\nReferences: 1 (Zacks)\\nScore: 0.00\r\n"
+    ));
+    assert!(output.contains(
+        "UID:20230418-NFLX@earnings-helper\r\n\
+        DTSTAMP:20231114T221320Z\r\n\
+        SEQUENCE:122163200\r\n\
+        DTSTART;TZID=America/New_York:20230418T160000\r\n\
+        DTEND;TZID=America/New_York:20230418T173000\r\n\
+        SUMMARY:NFLX earnings (AMC)\r\n"
+    ));
+}
+
+#[test]
+fn stable_uids_of_moved_reports() {
+    use crate::{
+        candidate::{eval_candidates, event},
+        parser::EarningsEvent,
+        symbol::SymbolNormalizer,
+    };
+
+    let uids = |day, fiscal_period: &[Option<&str>]| {
+        let data = fiscal_period
+            .iter()
+            .zip(["IBKR", "NFLX", "NVDA"])
+            .map(|(period, symbol)| EarningsEvent {
+                fiscal_period: period.map(str::to_owned),
+                ..event("Zacks", symbol, "")
+            })
+            .collect();
+        let days = [DayCandidates {
+            date: NaiveDate::from_ymd_opt(2023, 3, 31)
+                .unwrap()
+                .checked_add_days(Days::new(day))
+                .unwrap(),
+            attempted: vec!["Zacks"],
+            succeeded: vec!["Zacks"],
+            candidates: eval_candidates(data, 1, &SymbolNormalizer::default()),
+            date_changes: Vec::new(),
+            date_conflicts: Vec::new(),
+        }];
+        let stamp = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        ics_output(&days, stamp)
+            .lines()
+            .filter_map(|line| line.strip_prefix("UID:"))
+            .map(str::to_owned)
+            .collect::<Vec<_>>()
+    };
+
+    // The report moved from March 31 to April 2.
+    let periods = [None, Some("12/31/2022"), Some("Q1 2024")];
+    let before = uids(0, &periods);
+    assert_eq!(
+        before,
+        [
+            "20230331-IBKR@earnings-helper",
+            "2022Q4-NFLX@earnings-helper",
+            "FY2024Q1-NVDA@earnings-helper",
+        ]
+    );
+    let after = uids(2, &periods);
+    assert_eq!(after[0], "20230402-IBKR@earnings-helper");
+    assert_eq!(after[1..], before[1..]);
+    assert_eq!(uids(2, &[None, Some("2022-12-31")])[1], before[1]);
+}
@@ -0,0 +1,419 @@
+mod ics;
+
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::Serialize;
+
+use crate::{
+    candidate::{CompanyCandidate, DayCandidates},
+    conflict::DateConflict,
+    history::DateChange,
+    parser::ReportTiming,
+};
+
+/// Output file name used when no `--output` is given.
+pub const DEFAULT_OUTPUT: &str = "company_candidates.{ext}";
+/// Version of the CSV columns, increased whenever they change.
+const CSV_SCHEMA_VERSION: u32 = 2;
+
+/// Format of the file holding the evaluated candidates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+    Ics,
+}
+
+impl Format {
+    /// Names accepted by the `--format` option.
+    pub const NAMES: [&'static str; 4] = ["text", "json", "csv", "ics"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "text" => Some(Self::Text),
+            "json" => Some(Self::Json),
+            "csv" => Some(Self::Csv),
+            "ics" => Some(Self::Ics),
+            _ => None,
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Text => "txt",
+            Self::Json => "json",
+            Self::Csv => "csv",
+            Self::Ics => "ics",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonOutput<'a> {
+    generated: DateTime<Local>,
+    min_refs: usize,
+    dates: Vec<JsonDay<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonDay<'a> {
+    date: NaiveDate,
+    sources_attempted: &'a [&'static str],
+    sources_succeeded: &'a [&'static str],
+    candidates: Vec<JsonCandidate<'a>>,
+    date_changes: &'a [DateChange],
+    date_conflicts: &'a [DateConflict],
+}
+
+#[derive(Serialize)]
+struct JsonCandidate<'a> {
+    symbol: &'a str,
+    name: &'a str,
+    refs: usize,
+    score: f64,
+    sources: Vec<&'static str>,
+    merged_symbols: Vec<&'a str>,
+    timing: ReportTiming,
+    fiscal_period: Option<&'a str>,
+    eps_estimate: Option<&'a str>,
+    eps_reported: Option<&'a str>,
+    revenue_estimate: Option<&'a str>,
+    market_cap: Option<&'a str>,
+}
+
+impl<'a> JsonCandidate<'a> {
+    fn new(candidate: &'a CompanyCandidate) -> Self {
+        let event = &candidate.event;
+        Self {
+            symbol: &event.company.symbol,
+            name: &event.company.name,
+            refs: candidate.refs,
+            score: candidate.score,
+            sources: candidate.sources(),
+            merged_symbols: candidate.merged_symbols(),
+            timing: event.timing,
+            fiscal_period: event.fiscal_period.as_deref(),
+            eps_estimate: event.eps_estimate.as_deref(),
+            eps_reported: event.eps_reported.as_deref(),
+            revenue_estimate: event.revenue_estimate.as_deref(),
+            market_cap: event.market_cap.as_deref(),
+        }
+    }
+}
+
+/// Row of the CSV output. The order of the fields is the order
+/// of the columns.
+#[derive(Serialize)]
+struct CsvRow<'a> {
+    schema_version: u32,
+    date: NaiveDate,
+    symbol: &'a str,
+    name: &'a str,
+    refs: usize,
+    score: f64,
+    /// Listing websites separated by `;`.
+    sources: String,
+    timing: ReportTiming,
+    fiscal_period: Option<&'a str>,
+    eps_estimate: Option<&'a str>,
+    revenue_estimate: Option<&'a str>,
+    market_cap: Option<&'a str>,
+}
+
+/// Returns the output file path made from the `template`, replacing:
+/// - `{date}` with the target date, or `FIRST_LAST` for multiple dates
+/// - `{refs}` with the minimum number of references
+/// - `{time}` with the start time of the run
+/// - `{ext}` with the extension of the `format`
+///
+/// If the `template` is an existing directory, the file is placed
+/// inside of it with the default name.
+pub fn output_path(
+    template: &str,
+    dates: &[NaiveDate],
+    min_refs: usize,
+    format: Format,
+    started: DateTime<Local>,
+) -> PathBuf {
+    let template = if Path::new(template).is_dir() {
+        Path::new(template).join(DEFAULT_OUTPUT)
+    } else {
+        PathBuf::from(template)
+    };
+    let date = match dates {
+        [] => String::new(),
+        [date] => date.to_string(),
+        [first, .., last] => format!("{first}_{last}"),
+    };
+    let path = template
+        .to_string_lossy()
+        .replace("{date}", &date)
+        .replace("{refs}", &min_refs.to_string())
+        .replace("{time}", &started.format("%Y-%m-%d_%H-%M-%S").to_string())
+        .replace("{ext}", format.extension());
+    PathBuf::from(path)
+}
+
+/// Returns the candidates grouped by their earnings date
+/// in the `format`.
+pub fn data_output(
+    days: &[DayCandidates],
+    min_refs: usize,
+    format: Format,
+) -> anyhow::Result<String> {
+    Ok(match format {
+        Format::Text => text_output(days),
+        Format::Json => json_output(days, min_refs)?,
+        Format::Csv => csv_output(days)?,
+        Format::Ics => ics::ics_output(days, Utc::now()),
+    })
+}
+
+/// Writes the `output` into the file at `path` and returns
+/// its canonical path. Unless `overwrite` is set, an already
+/// existing file is left untouched and an error is returned.
+pub fn data_file_output(
+    path: &Path,
+    output: &str,
+    overwrite: bool,
+) -> anyhow::Result<PathBuf> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut file = if overwrite {
+        std::fs::File::create(path)?
+    } else {
+        std::fs::File::create_new(path).map_err(|e| {
+            anyhow::anyhow!("Couldn't create '{}': {e}", path.display())
+        })?
+    };
+    file.write_all(output.as_bytes())?;
+    Ok(std::fs::canonicalize(path)?)
+}
+
+fn text_output(days: &[DayCandidates]) -> String {
+    let mut output = String::new();
+    for day in days {
+        output.push_str(&format!("Date: {}\n", day.date));
+        output
+            .push_str("Refs.\tScore\tSymbol \tTime\tCompany Name [Sources]\n");
+        for d in day.candidates.iter() {
+            output.push_str(&format!(
+                "{:>5}\t{:.2}\t{:<7}\t{}\t{}  [{}]\n",
+                d.refs,
+                d.score,
+                d.event.company.symbol,
+                d.event.timing.abbreviation(),
+                d.event.company.name,
+                d.sources().join(", ")
+            ));
+        }
+        for change in day.date_changes.iter() {
+            output.push_str(&format!("Warning: {}\n", date_change(change)));
+        }
+        for conflict in day.date_conflicts.iter() {
+            output.push_str(&format!("Warning: {}\n", date_conflict(conflict)));
+        }
+        output.push('\n');
+    }
+    output
+}
+
+/// Describes the date change, e.g. `Date moved: NVDA listed by Zacks
+/// on 2024-05-22 in the run from 2024-05-20 08:00:00, now on 2024-05-23`.
+pub fn date_change(change: &DateChange) -> String {
+    format!(
+        "Date moved: {} listed by {} on {} in the run from {}, now on {}",
+        change.symbol,
+        change.source,
+        change.previous_date,
+        change.previously_listed.format("%Y-%m-%d %H:%M:%S"),
+        change.date
+    )
+}
+
+/// Describes the date conflict, e.g. `Date conflict: NVDA listed by
+/// Zacks on 2024-05-21, Benzinga on 2024-05-22; counted on 2024-05-22`.
+pub fn date_conflict(conflict: &DateConflict) -> String {
+    let listings: Vec<String> = conflict
+        .listings
+        .iter()
+        .map(|(source, date)| format!("{source} on {date}"))
+        .collect();
+    format!(
+        "Date conflict: {} listed by {}; counted on {}",
+        conflict.symbol,
+        listings.join(", "),
+        conflict.chosen
+    )
+}
+
+fn json_output(
+    days: &[DayCandidates],
+    min_refs: usize,
+) -> anyhow::Result<String> {
+    let output = JsonOutput {
+        generated: Local::now(),
+        min_refs,
+        dates: days
+            .iter()
+            .map(|day| JsonDay {
+                date: day.date,
+                sources_attempted: &day.attempted,
+                sources_succeeded: &day.succeeded,
+                candidates: day
+                    .candidates
+                    .iter()
+                    .map(JsonCandidate::new)
+                    .collect(),
+                date_changes: &day.date_changes,
+                date_conflicts: &day.date_conflicts,
+            })
+            .collect(),
+    };
+    Ok(serde_json::to_string_pretty(&output)?)
+}
+
+/// Writes one row per candidate, with a header row, following
+/// RFC 4180.
+fn csv_output(days: &[DayCandidates]) -> anyhow::Result<String> {
+    let mut writer = csv::WriterBuilder::new()
+        .terminator(csv::Terminator::CRLF)
+        .from_writer(Vec::new());
+    for day in days {
+        for candidate in day.candidates.iter() {
+            let event = &candidate.event;
+            writer.serialize(CsvRow {
+                schema_version: CSV_SCHEMA_VERSION,
+                date: day.date,
+                symbol: &event.company.symbol,
+                name: &event.company.name,
+                refs: candidate.refs,
+                score: candidate.score,
+                sources: candidate.sources().join(";"),
+                timing: event.timing,
+                fiscal_period: event.fiscal_period.as_deref(),
+                eps_estimate: event.eps_estimate.as_deref(),
+                revenue_estimate: event.revenue_estimate.as_deref(),
+                market_cap: event.market_cap.as_deref(),
+            })?;
+        }
+    }
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+#[cfg(test)]
+fn test_days() -> Vec<DayCandidates> {
+    use crate::{
+        candidate::{eval_candidates, event},
+        parser::EarningsEvent,
+        score::{score_candidates, SourceWeights},
+        symbol::SymbolNormalizer,
+    };
+    use std::collections::HashMap;
+
+    let event = |source, symbol, name| EarningsEvent {
+        timing: ReportTiming::AfterClose,
+        ..event(source, symbol, name)
+    };
+    let data = vec![
+        event("Zacks", "NFLX", "Netflix, Inc."),
+        event("Investing", "SCHW", "Charles \"Schwab\"\tCorp"),
+        event("Benzinga", "NFLX", ""),
+    ];
+    let succeeded = vec!["Zacks", "Benzinga"];
+    let mut candidates = eval_candidates(data, 1, &SymbolNormalizer::default());
+    let weights =
+        SourceWeights::new(&HashMap::from([("Investing".to_owned(), 2.0)]));
+    score_candidates(&mut candidates, &weights, &succeeded, 0.0);
+    vec![DayCandidates {
+        date: NaiveDate::from_ymd_opt(2023, 4, 18).unwrap(),
+        attempted: vec!["Zacks", "Benzinga", "TradingView"],
+        succeeded,
+        candidates,
+        date_changes: Vec::new(),
+        date_conflicts: Vec::new(),
+    }]
+}
+
+#[test]
+fn text_output_rows() {
+    assert_eq!(
+        text_output(&test_days()),
+        "Date: 2023-04-18\n\
+        Refs.\tScore\tSymbol \tTime\tCompany Name [Sources]\n    \
+        2\t1.00\tNFLX   \tAMC\tNetflix, Inc.  [Zacks, Benzinga]\n    \
+        1\t0.50\tSCHW   \tAMC\tCharles \"Schwab\"\tCorp  [Investing]\n\n"
+    );
+}
+
+#[test]
+fn json_output_document() {
+    let output = json_output(&test_days(), 2).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json["min_refs"], 2);
+
+    let day = &json["dates"][0];
+    assert_eq!(day["date"], "2023-04-18");
+    assert_eq!(
+        day["sources_attempted"],
+        serde_json::json!(["Zacks", "Benzinga", "TradingView"])
+    );
+    assert_eq!(
+        day["sources_succeeded"],
+        serde_json::json!(["Zacks", "Benzinga"])
+    );
+
+    let candidate = &day["candidates"][0];
+    assert_eq!(candidate["symbol"], "NFLX");
+    assert_eq!(candidate["name"], "Netflix, Inc.");
+    assert_eq!(candidate["refs"], 2);
+    assert_eq!(candidate["score"], 1.0);
+    assert_eq!(
+        candidate["sources"],
+        serde_json::json!(["Zacks", "Benzinga"])
+    );
+    assert_eq!(candidate["timing"], "after_close");
+    assert!(candidate["eps_estimate"].is_null());
+}
+
+#[test]
+fn csv_output_rows() {
+    assert_eq!(
+        csv_output(&test_days()).unwrap(),
+        "schema_version,date,symbol,name,refs,score,sources,timing,\
+        fiscal_period,eps_estimate,revenue_estimate,market_cap\r\n\
+        2,2023-04-18,NFLX,\"Netflix, Inc.\",2,1.0,Zacks;Benzinga,\
+        after_close,,,,\r\n\
+        2,2023-04-18,SCHW,\"Charles \"\"Schwab\"\"\tCorp\",1,0.5,\
+        Investing,after_close,,,,\r\n"
+    );
+}
+
+#[test]
+fn output_path_templates() {
+    let started = Local::now();
+    let date = NaiveDate::from_ymd_opt(2023, 4, 18).unwrap();
+    let path = |template, dates: &[NaiveDate]| {
+        output_path(template, dates, 2, Format::Json, started)
+    };
+    assert_eq!(
+        path(DEFAULT_OUTPUT, &[date]),
+        Path::new("company_candidates.json")
+    );
+    assert_eq!(
+        path("out/{date}_{refs}.{ext}", &[date]),
+        Path::new("out/2023-04-18_2.json")
+    );
+    let last = NaiveDate::from_ymd_opt(2023, 4, 21).unwrap();
+    assert_eq!(
+        path("{date}.json", &[date, last]),
+        Path::new("2023-04-18_2023-04-21.json")
+    );
+    assert_eq!(path(".", &[date]), Path::new("./company_candidates.json"));
+}
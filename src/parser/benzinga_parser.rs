@@ -1,15 +1,15 @@
 use async_trait::async_trait;
-use chrono::Datelike;
+use chrono::{Datelike, NaiveDate};
 use thirtyfour::{
     prelude::{ElementQueryable, ElementWaitable},
     By, WebDriver,
 };
 
 use super::{
-    Company, WebsiteParser, BENZINGA, LOAD_WAIT_SHORT, SCROLL_INTO_VIEW,
+    event::select_text, selector, Company, EarningsEvent, Navigation,
+    ReportTiming, WebsiteParser, BENZINGA, LOAD_WAIT_SHORT, SCROLL_INTO_VIEW,
     TIMEOUT_FIVE_SEC, TIMEOUT_TEN_SEC, WAIT_INTERVAL,
 };
-use crate::RelativeDay;
 
 const POPUP_CLOSE_BUTTON1_SELECTOR: &str =
     "button[class=\"StyledIconButtonElement-sc-114x20d-0 jdccT prosper-field-button\"";
@@ -19,10 +19,16 @@ const POPUP_CLOSE_BUTTON3_SELECTOR: &str =
     "hedrick-ButtonElement--i1vL3d8rL0altqhu34kO";
 const PREVIOUS_MONTH_BUTTON_SELECTOR: &str =
     "span[class=\"DayPicker-NavButton DayPicker-NavButton--prev\"]";
+const NEXT_MONTH_BUTTON_SELECTOR: &str =
+    "span[class=\"DayPicker-NavButton DayPicker-NavButton--next\"]";
 const DATE_PICKER_SELECTOR: &str =
     "div[class=\"range-date-picker__field-wrapper\"]";
-const SYMBOL_SELECTOR: &str =
-    "tr[class=\"ant-table-row ant-table-row-level-0\"]>td:nth-child(3)>div>div>div>a";
+const ROW_SELECTOR: &str = "tr[class=\"ant-table-row ant-table-row-level-0\"]";
+const TIME_SELECTOR: &str = "td:nth-child(2)";
+const SYMBOL_SELECTOR: &str = "td:nth-child(3)>div>div>div>a";
+const QUARTER_SELECTOR: &str = "td:nth-child(4)";
+const EPS_ESTIMATE_SELECTOR: &str = "td:nth-child(6)";
+const REVENUE_ESTIMATE_SELECTOR: &str = "td:nth-child(7)";
 
 pub struct BenzingaParser {}
 
@@ -30,13 +36,13 @@ pub struct BenzingaParser {}
 impl WebsiteParser for BenzingaParser {
     const NAME: &'static str = "Benzinga";
 
-    async fn parse(
+    async fn navigate(
         driver: &WebDriver,
-        day: RelativeDay,
-    ) -> anyhow::Result<Vec<Company>> {
+        date: NaiveDate,
+    ) -> anyhow::Result<Navigation> {
         driver.goto(BENZINGA).await?;
         loop {
-            if pick_date(driver, day).await.is_err() {
+            if pick_date(driver, date).await.is_err() {
                 close_popup(driver).await?;
                 continue;
             }
@@ -45,13 +51,49 @@ impl WebsiteParser for BenzingaParser {
         // Wait for the results to load
         tokio::time::sleep(LOAD_WAIT_SHORT).await;
 
-        parse_data(driver).await
+        Ok(Navigation::Loaded)
+    }
+
+    fn extract(
+        html: &str,
+        _date: NaiveDate,
+    ) -> anyhow::Result<Vec<EarningsEvent>> {
+        let document = scraper::Html::parse_document(html);
+        let row_selector = selector(ROW_SELECTOR)?;
+        let time_selector = selector(TIME_SELECTOR)?;
+        let symbol_selector = selector(SYMBOL_SELECTOR)?;
+        let quarter_selector = selector(QUARTER_SELECTOR)?;
+        let eps_estimate_selector = selector(EPS_ESTIMATE_SELECTOR)?;
+        let revenue_estimate_selector = selector(REVENUE_ESTIMATE_SELECTOR)?;
+
+        let mut events = Vec::new();
+        for row in document.select(&row_selector) {
+            let Some(symbol) = row.select(&symbol_selector).next() else {
+                continue;
+            };
+            // Benzinga doesn't list company names.
+            let company = Company {
+                symbol: symbol.inner_html(),
+                name: String::new(),
+            };
+
+            let mut event = EarningsEvent::new(Self::NAME, company);
+            event.timing = select_text(row, &time_selector)
+                .map_or(ReportTiming::Unknown, |t| ReportTiming::from_text(&t));
+            event.fiscal_period = select_text(row, &quarter_selector);
+            event.eps_estimate = select_text(row, &eps_estimate_selector);
+            event.revenue_estimate =
+                select_text(row, &revenue_estimate_selector);
+            events.push(event);
+        }
+
+        Ok(events)
     }
 }
 
 async fn pick_date(
     driver: &WebDriver,
-    target: RelativeDay,
+    target_date: NaiveDate,
 ) -> anyhow::Result<()> {
     let calendar = driver
         .query(By::Css(DATE_PICKER_SELECTOR))
@@ -65,11 +107,13 @@ async fn pick_date(
     calendar.click().await?;
 
     let today = chrono::Local::now().date_naive();
-    let target_date = target.get_date();
-    if today.month() != target_date.month() {
-        if let RelativeDay::Yesterday = target {
-            to_previous_month(driver).await?;
-        }
+    let months = month_index(target_date) - month_index(today);
+    // The day picker shows the current and the next month.
+    for _ in 0..-months {
+        to_previous_month(driver).await?;
+    }
+    for _ in 1..months {
+        to_next_month(driver).await?;
     }
     let formatted_date = target_date.format("%a %b %d %Y").to_string();
     let day_picker_selector =
@@ -101,6 +145,26 @@ async fn to_previous_month(driver: &WebDriver) -> anyhow::Result<()> {
     Ok(())
 }
 
+async fn to_next_month(driver: &WebDriver) -> anyhow::Result<()> {
+    let button = driver
+        .query(By::Css(NEXT_MONTH_BUTTON_SELECTOR))
+        .wait(TIMEOUT_FIVE_SEC, WAIT_INTERVAL)
+        .desc("Find 'next month' button")
+        .single()
+        .await?;
+    driver
+        .execute(SCROLL_INTO_VIEW, vec![button.to_json()?])
+        .await?;
+    button.click().await?;
+    Ok(())
+}
+
+/// Number of months since the year 0, used for counting
+/// the months between two dates.
+fn month_index(date: NaiveDate) -> i32 {
+    date.year() * 12 + date.month0() as i32
+}
+
 async fn close_popup(driver: &WebDriver) -> anyhow::Result<()> {
     let button = driver
         .query(By::Css(POPUP_CLOSE_BUTTON1_SELECTOR))
@@ -122,34 +186,41 @@ async fn close_popup(driver: &WebDriver) -> anyhow::Result<()> {
     Ok(())
 }
 
-async fn parse_data(driver: &WebDriver) -> anyhow::Result<Vec<Company>> {
-    let source = driver.source().await?;
-    let document = scraper::Html::parse_document(&source);
-    let symbol_selector = scraper::Selector::parse(SYMBOL_SELECTOR)
-        .map_err(|e| eprintln!("{e}"))
-        .unwrap();
-    let symbols: Vec<String> = document
-        .select(&symbol_selector)
-        .map(|e| e.inner_html())
-        .collect();
-
-    let companies: Vec<Company> = symbols
-        .iter()
-        .map(|s| Company {
-            symbol: s.clone(),
-            name: String::new(),
-        })
-        .collect();
-    Ok(companies)
-}
-
 #[test]
 fn date_weekday_month_day_year() {
-    let date = chrono::NaiveDate::from_ymd_opt(2023, 4, 12).unwrap();
+    let date = NaiveDate::from_ymd_opt(2023, 4, 12).unwrap();
     let formatted = date.format("%a %b %d %Y").to_string();
     assert_eq!(formatted, "Wed Apr 12 2023");
 
-    let date = chrono::NaiveDate::from_ymd_opt(2023, 4, 14).unwrap();
+    let date = NaiveDate::from_ymd_opt(2023, 4, 14).unwrap();
     let formatted = date.format("%a %b %d %Y").to_string();
     assert_eq!(formatted, "Fri Apr 14 2023");
 }
+
+#[test]
+fn extract_fixture() {
+    let html = include_str!("fixtures/benzinga.html");
+    let date = NaiveDate::from_ymd_opt(2023, 4, 18).unwrap();
+    let events = BenzingaParser::extract(html, date).unwrap();
+    assert_eq!(
+        events
+            .iter()
+            .map(|e| (e.company.symbol.as_str(), e.company.name.as_str()))
+            .collect::<Vec<_>>(),
+        [("NFLX", ""), ("JNJ", ""), ("IBKR", "")]
+    );
+    let nflx = &events[0];
+    assert_eq!(nflx.timing, ReportTiming::AfterClose);
+    assert_eq!(nflx.fiscal_period.as_deref(), Some("Q1 2023"));
+    assert_eq!(events[1].timing, ReportTiming::BeforeOpen);
+    assert_eq!(events[2].timing, ReportTiming::Unknown);
+}
+
+#[test]
+fn months_between_dates() {
+    let today = NaiveDate::from_ymd_opt(2023, 12, 31).unwrap();
+    let target = NaiveDate::from_ymd_opt(2024, 2, 1).unwrap();
+    assert_eq!(month_index(target) - month_index(today), 2);
+    let target = NaiveDate::from_ymd_opt(2023, 11, 30).unwrap();
+    assert_eq!(month_index(target) - month_index(today), -1);
+}
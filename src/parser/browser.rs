@@ -0,0 +1,152 @@
+use serde::Deserialize;
+use thirtyfour::{
+    common::capabilities::firefox::FirefoxPreferences, Capabilities,
+    CapabilitiesHelper, ChromiumLikeCapabilities, DesiredCapabilities, Proxy,
+};
+
+use crate::config::WebDriverConfig;
+
+/// Browser driven through the WebDriver.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Browser {
+    #[default]
+    Chrome,
+    Firefox,
+}
+
+impl Browser {
+    pub const NAMES: [&'static str; 2] = ["chrome", "firefox"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "chrome" => Some(Self::Chrome),
+            "firefox" => Some(Self::Firefox),
+            _ => None,
+        }
+    }
+
+    /// Name of the WebDriver binary of the browser.
+    pub fn driver_name(&self) -> &'static str {
+        match self {
+            Self::Chrome => "chromedriver",
+            Self::Firefox => "geckodriver",
+        }
+    }
+
+    /// Endpoint of the WebDriver started with its default port.
+    pub fn default_url(&self) -> &'static str {
+        match self {
+            Self::Chrome => "http://localhost:9515",
+            Self::Firefox => "http://localhost:4444",
+        }
+    }
+
+    /// Whether a WebDriver process can only run one session at a time,
+    /// like geckodriver.
+    pub fn single_session(&self) -> bool {
+        *self == Self::Firefox
+    }
+}
+
+/// Capabilities requested for every browser session, made from
+/// the `[webdriver]` settings of the config file.
+pub fn capabilities(
+    config: &WebDriverConfig,
+    headless: bool,
+) -> anyhow::Result<Capabilities> {
+    // Both browsers use the proxy for plain and secure connections.
+    let proxy = config.proxy.as_ref().map(|proxy| Proxy::Manual {
+        ftp_proxy: None,
+        http_proxy: Some(proxy.clone()),
+        ssl_proxy: Some(proxy.clone()),
+        socks_proxy: None,
+        socks_version: None,
+        socks_username: None,
+        socks_password: None,
+        no_proxy: None,
+    });
+
+    let caps = match config.browser {
+        Browser::Chrome => {
+            let mut caps = DesiredCapabilities::chrome();
+            if headless {
+                caps.set_headless()?;
+            }
+            if let Some(user_agent) = &config.user_agent {
+                caps.add_arg(&format!("--user-agent={user_agent}"))?;
+            }
+            if let Some([width, height]) = config.window_size {
+                caps.add_arg(&format!("--window-size={width},{height}"))?;
+            }
+            if let Some(proxy) = proxy {
+                caps.set_proxy(proxy)?;
+            }
+            for arg in config.chrome_args.iter() {
+                caps.add_arg(arg)?;
+            }
+            caps.into()
+        }
+        Browser::Firefox => {
+            let mut caps = DesiredCapabilities::firefox();
+            if headless {
+                caps.set_headless()?;
+            }
+            if let Some(user_agent) = &config.user_agent {
+                let mut preferences = FirefoxPreferences::new();
+                preferences.set_user_agent(user_agent.clone())?;
+                caps.set_preferences(preferences)?;
+            }
+            if let Some([width, height]) = config.window_size {
+                caps.add_arg(&format!("--width={width}"))?;
+                caps.add_arg(&format!("--height={height}"))?;
+            }
+            if let Some(proxy) = proxy {
+                caps.set_proxy(proxy)?;
+            }
+            caps.into()
+        }
+    };
+    Ok(caps)
+}
+
+#[test]
+fn configured_capabilities() {
+    let mut config: WebDriverConfig = toml::from_str(
+        r#"
+        user_agent = "Mozilla/5.0"
+        window_size = [1920, 1080]
+        proxy = "proxy.local:3128"
+        chrome_args = ["--disable-gpu"]
+        "#,
+    )
+    .unwrap();
+
+    let caps = capabilities(&config, true).unwrap();
+    assert_eq!(caps["browserName"], "chrome");
+    assert_eq!(
+        caps["goog:chromeOptions"]["args"],
+        serde_json::json!([
+            "--headless",
+            "--user-agent=Mozilla/5.0",
+            "--window-size=1920,1080",
+            "--disable-gpu",
+        ])
+    );
+    assert_eq!(caps["proxy"]["httpProxy"], "proxy.local:3128");
+    assert_eq!(caps["proxy"]["sslProxy"], "proxy.local:3128");
+
+    config.browser = Browser::Firefox;
+    let caps = capabilities(&config, false).unwrap();
+    assert_eq!(caps["browserName"], "firefox");
+    let options = &caps["moz:firefoxOptions"];
+    assert_eq!(
+        options["args"],
+        serde_json::json!(["--width=1920", "--height=1080"])
+    );
+    assert_eq!(
+        options["prefs"]["general.useragent.override"],
+        "Mozilla/5.0"
+    );
+    assert_eq!(caps["proxy"]["proxyType"], "manual");
+}
@@ -0,0 +1,133 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local, NaiveDate};
+use serde::Serialize;
+use thirtyfour::WebDriver;
+
+use super::Parser;
+
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// Saves the final page of every visited website into a
+/// timestamped run directory together with a manifest describing
+/// the run. The run directory can later be used for replaying.
+pub struct Capture {
+    dir: PathBuf,
+    screenshots: bool,
+    manifest: Manifest,
+}
+
+#[derive(Debug, Serialize)]
+struct Manifest {
+    created: DateTime<Local>,
+    sources: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Serialize)]
+struct ManifestEntry {
+    source: &'static str,
+    url: Option<String>,
+    target_date: NaiveDate,
+    entries: Option<usize>,
+    success: bool,
+    error: Option<String>,
+    snapshot: Option<String>,
+    screenshot: Option<String>,
+}
+
+impl Capture {
+    /// Creates a new run directory inside of `root`.
+    pub fn new(root: &Path, screenshots: bool) -> anyhow::Result<Self> {
+        let created = Local::now();
+        let dir = root.join(created.format("%Y-%m-%d_%H-%M-%S").to_string());
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            screenshots,
+            manifest: Manifest {
+                created,
+                sources: Vec::new(),
+            },
+        })
+    }
+
+    /// Name of the directory holding pages captured for the `date`.
+    pub(super) fn date_dir(date: NaiveDate) -> String {
+        date.format("%Y-%m-%d").to_string()
+    }
+
+    /// Saves the `source` of the page loaded for the `date` and records
+    /// the parsing `outcome` of the website in the manifest. The `source`
+    /// is only missing when parsing failed, then the page currently
+    /// shown by the `driver` is saved. Without either only the outcome
+    /// is recorded.
+    ///
+    /// The page is saved even if parsing failed since it's the
+    /// best evidence of what went wrong.
+    pub(super) async fn save(
+        &mut self,
+        driver: Option<&WebDriver>,
+        parser: Parser,
+        date: NaiveDate,
+        source: Option<&str>,
+        outcome: Result<usize, String>,
+    ) -> anyhow::Result<()> {
+        let mut entry = ManifestEntry {
+            source: parser.get_name(),
+            url: None,
+            target_date: date,
+            entries: outcome.as_ref().ok().copied(),
+            success: outcome.is_ok(),
+            error: outcome.err(),
+            snapshot: None,
+            screenshot: None,
+        };
+        let saved = self.save_page(driver, parser, source, &mut entry).await;
+        self.manifest.sources.push(entry);
+        saved
+    }
+
+    async fn save_page(
+        &self,
+        driver: Option<&WebDriver>,
+        parser: Parser,
+        source: Option<&str>,
+        entry: &mut ManifestEntry,
+    ) -> anyhow::Result<()> {
+        if let Some(driver) = driver {
+            entry.url = Some(driver.current_url().await?.to_string());
+        }
+        let source = match (source, driver) {
+            (Some(source), _) => source.to_owned(),
+            (None, Some(driver)) => driver.source().await?,
+            (None, None) => return Ok(()),
+        };
+
+        // Paths in the manifest are relative to the run directory.
+        let date_dir = Self::date_dir(entry.target_date);
+        std::fs::create_dir_all(self.dir.join(&date_dir))?;
+
+        let snapshot = format!("{}/{}", date_dir, parser.snapshot_file_name());
+        std::fs::write(self.dir.join(&snapshot), source)?;
+        entry.snapshot = Some(snapshot);
+
+        if let Some(driver) = driver.filter(|_| self.screenshots) {
+            let screenshot = format!(
+                "{}/{}.png",
+                date_dir,
+                parser.get_name().to_lowercase()
+            );
+            driver.screenshot(&self.dir.join(&screenshot)).await?;
+            entry.screenshot = Some(screenshot);
+        }
+        Ok(())
+    }
+
+    /// Writes the manifest file into the run directory.
+    pub fn write_manifest(&self) -> anyhow::Result<PathBuf> {
+        let path = self.dir.join(MANIFEST_FILE_NAME);
+        let manifest = serde_json::to_string_pretty(&self.manifest)?;
+        std::fs::write(&path, manifest)?;
+        Ok(path)
+    }
+}
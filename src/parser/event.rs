@@ -0,0 +1,127 @@
+use scraper::{ElementRef, Selector};
+use serde::Serialize;
+
+use super::Company;
+
+/// Single earnings report of a company listed by a website.
+#[derive(Debug, Clone)]
+pub struct EarningsEvent {
+    /// Name of the website listing the event.
+    pub source: &'static str,
+    pub company: Company,
+    pub timing: ReportTiming,
+    pub fiscal_period: Option<String>,
+    pub eps_estimate: Option<String>,
+    /// Actual EPS, listed by some websites once the company reported.
+    pub eps_reported: Option<String>,
+    pub revenue_estimate: Option<String>,
+    pub market_cap: Option<String>,
+}
+
+impl EarningsEvent {
+    /// Creates an event without any additional details.
+    pub fn new(source: &'static str, company: Company) -> Self {
+        Self {
+            source,
+            company,
+            timing: ReportTiming::Unknown,
+            fiscal_period: None,
+            eps_estimate: None,
+            eps_reported: None,
+            revenue_estimate: None,
+            market_cap: None,
+        }
+    }
+
+    /// Fills in the details this event is missing from the `other` event.
+    pub fn merge(&mut self, other: &EarningsEvent) {
+        if self.company.name.is_empty() {
+            self.company.name.clone_from(&other.company.name);
+        }
+        if self.timing == ReportTiming::Unknown {
+            self.timing = other.timing;
+        }
+        let fields = [
+            (&mut self.fiscal_period, &other.fiscal_period),
+            (&mut self.eps_estimate, &other.eps_estimate),
+            (&mut self.eps_reported, &other.eps_reported),
+            (&mut self.revenue_estimate, &other.revenue_estimate),
+            (&mut self.market_cap, &other.market_cap),
+        ];
+        for (field, other) in fields {
+            if field.is_none() {
+                field.clone_from(other);
+            }
+        }
+    }
+}
+
+/// Time of the day when the company reports its earnings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportTiming {
+    BeforeOpen,
+    DuringMarket,
+    AfterClose,
+    Unknown,
+}
+
+impl ReportTiming {
+    /// Recognizes the timing from the different notations used by
+    /// websites, e.g. 'Before market open', 'BMO' or 'After Close'.
+    pub fn from_text(text: &str) -> Self {
+        let text = text.trim().to_lowercase();
+        if text.starts_with("before") || text == "bmo" || text == "pre-market" {
+            Self::BeforeOpen
+        } else if text.starts_with("after") || text == "amc" {
+            Self::AfterClose
+        } else if text.starts_with("during") || text == "dmh" || text == "dmt" {
+            Self::DuringMarket
+        } else {
+            Self::Unknown
+        }
+    }
+
+    /// Short notation used in the output.
+    pub fn abbreviation(&self) -> &'static str {
+        match self {
+            Self::BeforeOpen => "BMO",
+            Self::DuringMarket => "DMT",
+            Self::AfterClose => "AMC",
+            Self::Unknown => "-",
+        }
+    }
+}
+
+/// Returns the trimmed text of the first element matching the
+/// `selector` inside of the `element`. Empty cells and placeholders
+/// like `--` are returned as `None`.
+pub(super) fn select_text(
+    element: ElementRef,
+    selector: &Selector,
+) -> Option<String> {
+    let text = element.select(selector).next()?.text().collect::<String>();
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match text.as_str() {
+        "" | "-" | "--" | "n/a" | "N/A" => None,
+        _ => Some(text),
+    }
+}
+
+#[test]
+fn report_timing_notations() {
+    assert_eq!(
+        ReportTiming::from_text("Before market open"),
+        ReportTiming::BeforeOpen
+    );
+    assert_eq!(ReportTiming::from_text(" AMC "), ReportTiming::AfterClose);
+    assert_eq!(
+        ReportTiming::from_text("After Close"),
+        ReportTiming::AfterClose
+    );
+    assert_eq!(
+        ReportTiming::from_text("During Market Trading"),
+        ReportTiming::DuringMarket
+    );
+    assert_eq!(ReportTiming::from_text("--"), ReportTiming::Unknown);
+}
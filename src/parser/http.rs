@@ -0,0 +1,22 @@
+use reqwest::{Client, Proxy};
+
+use super::TIMEOUT_TEN_SEC;
+use crate::config::WebDriverConfig;
+
+/// Sent unless another user agent is configured, since the websites
+/// refuse requests which don't look like they come from a browser.
+const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (X11; Linux x86_64) \
+    AppleWebKit/537.36 (KHTML, like Gecko) Chrome/126.0 Safari/537.36";
+
+/// HTTP client of the websites fetched without a browser, using
+/// the user agent and proxy of the `[webdriver]` settings.
+pub fn http_client(config: &WebDriverConfig) -> anyhow::Result<Client> {
+    let user_agent = config.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT);
+    let mut builder = Client::builder()
+        .user_agent(user_agent)
+        .timeout(TIMEOUT_TEN_SEC);
+    if let Some(proxy) = &config.proxy {
+        builder = builder.proxy(Proxy::all(format!("http://{proxy}"))?);
+    }
+    Ok(builder.build()?)
+}
@@ -1,12 +1,15 @@
 use async_trait::async_trait;
+use chrono::NaiveDate;
+use reqwest::Client;
+use serde::Deserialize;
 use std::vec;
 use thirtyfour::{prelude::ElementQueryable, By, WebDriver};
 
 use super::{
-    Company, WebsiteParser, INVESTING, LOAD_WAIT_SHORT, SCROLL_INTO_VIEW,
-    TIMEOUT_FIVE_SEC, WAIT_INTERVAL,
+    event::select_text, selector, Backend, Company, EarningsEvent, Navigation,
+    ReportTiming, WebsiteParser, INVESTING, INVESTING_CALENDAR,
+    LOAD_WAIT_SHORT, SCROLL_INTO_VIEW, TIMEOUT_FIVE_SEC, WAIT_INTERVAL,
 };
-use crate::RelativeDay;
 
 const COOKIE_ACCEPT_ID: &str = "onetrust-accept-btn-handler";
 //const POPUP_CLOSE_BUTTON_SELECTOR: &str =
@@ -14,19 +17,56 @@ const COOKIE_ACCEPT_ID: &str = "onetrust-accept-btn-handler";
 const PREVIOUS_DAY_ID: &str = "timeFrame_yesterday";
 const SYMBOL_SELECTOR: &str = "a[class=\"bold middle\"]";
 const COMPANY_NAME_SELECTOR: &str = "span[class=\"earnCalCompanyName middle\"]";
+const ROW_SELECTOR: &str = "tbody>tr";
+const EPS_ACTUAL_SELECTOR: &str = "td.eps_actual";
+const EPS_FORECAST_SELECTOR: &str = "td:nth-child(4)";
+const REVENUE_FORECAST_SELECTOR: &str = "td:nth-child(6)";
+const MARKET_CAP_SELECTOR: &str = "td:nth-child(7)";
+const TIME_SELECTOR: &str = "td.time>span";
 const TODAY_DAY_ID: &str = "timeFrame_today";
 const NEXT_DAY_ID: &str = "timeFrame_tomorrow";
+const DATE_PICKER_ID: &str = "datePickerToggleBtn";
+const START_DATE_ID: &str = "startDate";
+const END_DATE_ID: &str = "endDate";
+const APPLY_DATE_ID: &str = "applyBtn";
+const SET_INPUT_VALUE: &str = "arguments[0].value = arguments[1];";
+/// Investing's id of the United States in the country filter.
+const US_COUNTRY_ID: &str = "5";
+/// Limit of the requested batches of rows, in case the calendar
+/// keeps asking for more. Reaching it fails the fetch.
+const MAX_BATCHES: usize = 20;
 
 pub struct InvestingParser {}
 
+/// Batch of calendar rows returned by the calendar service.
+#[derive(Debug, Deserialize)]
+struct CalendarData {
+    /// Table rows in the markup of the calendar page.
+    data: String,
+    /// Whether the page would request the next batch when scrolled.
+    #[serde(default)]
+    bind_scroll_handler: bool,
+}
+
 #[async_trait]
 impl WebsiteParser for InvestingParser {
     const NAME: &'static str = "Investing";
+    const BACKEND: Backend = Backend::Http;
+
+    async fn fetch(
+        client: &Client,
+        date: NaiveDate,
+    ) -> anyhow::Result<Option<String>> {
+        fetch_calendar(client, INVESTING_CALENDAR, date)
+            .await
+            .map(Some)
+    }
 
-    async fn parse(
+    /// Used when fetching the calendar without a browser fails.
+    async fn navigate(
         driver: &WebDriver,
-        day: RelativeDay,
-    ) -> anyhow::Result<Vec<Company>> {
+        date: NaiveDate,
+    ) -> anyhow::Result<Navigation> {
         driver.goto(INVESTING).await?;
         // Accept cookies in order to remove the cookies 'obstacle' dialog box.
         accept_cookies(driver).await.unwrap_or(());
@@ -34,15 +74,70 @@ impl WebsiteParser for InvestingParser {
         // Close the popup if it appears
         //close_popup(driver).await.unwrap_or(());
 
-        match day {
-            RelativeDay::Yesterday => to_previous_day(driver).await?,
-            RelativeDay::Today => to_today_day(driver).await?,
-            RelativeDay::Tomorrow => to_next_day(driver).await?,
+        let today = chrono::offset::Local::now().date_naive();
+        match (date - today).num_days() {
+            -1 => to_previous_day(driver).await?,
+            0 => to_today_day(driver).await?,
+            1 => to_next_day(driver).await?,
+            _ => pick_date(driver, date).await?,
         }
         // Wait for the browser to load data table
         tokio::time::sleep(LOAD_WAIT_SHORT).await;
 
-        parse_data(driver).await
+        Ok(Navigation::Loaded)
+    }
+
+    fn extract(
+        html: &str,
+        _date: NaiveDate,
+    ) -> anyhow::Result<Vec<EarningsEvent>> {
+        let document = scraper::Html::parse_document(html);
+        let row_selector = selector(ROW_SELECTOR)?;
+        let symbol_selector = selector(SYMBOL_SELECTOR)?;
+        let names_selector = selector(COMPANY_NAME_SELECTOR)?;
+        let eps_actual_selector = selector(EPS_ACTUAL_SELECTOR)?;
+        let eps_forecast_selector = selector(EPS_FORECAST_SELECTOR)?;
+        let revenue_forecast_selector = selector(REVENUE_FORECAST_SELECTOR)?;
+        let market_cap_selector = selector(MARKET_CAP_SELECTOR)?;
+        let time_selector = selector(TIME_SELECTOR)?;
+        // Forecasts are written after the actual values, e.g. '/  2.5'.
+        let forecast = |text: String| {
+            Some(text.trim_start_matches('/').trim().to_string())
+                .filter(|t| !t.is_empty() && t != "--")
+        };
+
+        let mut events = Vec::new();
+        for row in document.select(&row_selector) {
+            // Skip the day header rows.
+            let Some(symbol) = row.select(&symbol_selector).next() else {
+                continue;
+            };
+            let symbol = symbol.inner_html();
+            let name = row
+                .select(&names_selector)
+                .next()
+                .map(|e| e.inner_html())
+                .ok_or_else(|| {
+                    anyhow::anyhow!("Found no company name for '{symbol}'")
+                })?;
+
+            let mut event =
+                EarningsEvent::new(Self::NAME, Company { symbol, name });
+            event.timing = row
+                .select(&time_selector)
+                .next()
+                .and_then(|e| e.value().attr("data-tooltip"))
+                .map_or(ReportTiming::Unknown, ReportTiming::from_text);
+            event.eps_estimate =
+                select_text(row, &eps_forecast_selector).and_then(forecast);
+            event.eps_reported = select_text(row, &eps_actual_selector);
+            event.revenue_estimate =
+                select_text(row, &revenue_forecast_selector).and_then(forecast);
+            event.market_cap = select_text(row, &market_cap_selector);
+            events.push(event);
+        }
+
+        Ok(events)
     }
 }
 
@@ -88,6 +183,45 @@ async fn to_next_day(driver: &WebDriver) -> anyhow::Result<()> {
     button.click().await?;
     Ok(())
 }
+/// Sets both ends of the date picker range to the target `date`.
+async fn pick_date(driver: &WebDriver, date: NaiveDate) -> anyhow::Result<()> {
+    let button = driver
+        .query(By::Id(DATE_PICKER_ID))
+        .wait(TIMEOUT_FIVE_SEC, WAIT_INTERVAL)
+        .desc("Find the date picker button")
+        .single()
+        .await?;
+    driver
+        .execute(SCROLL_INTO_VIEW, vec![button.to_json()?])
+        .await?;
+    button.click().await?;
+
+    let formatted_date = date.format("%m/%d/%Y").to_string();
+    for id in [START_DATE_ID, END_DATE_ID] {
+        let input = driver
+            .query(By::Id(id))
+            .wait(TIMEOUT_FIVE_SEC, WAIT_INTERVAL)
+            .desc("Find the date range input")
+            .single()
+            .await?;
+        driver
+            .execute(
+                SET_INPUT_VALUE,
+                vec![input.to_json()?, formatted_date.clone().into()],
+            )
+            .await?;
+    }
+    driver
+        .query(By::Id(APPLY_DATE_ID))
+        .wait(TIMEOUT_FIVE_SEC, WAIT_INTERVAL)
+        .desc("Find the 'Apply' button")
+        .single()
+        .await?
+        .click()
+        .await?;
+    Ok(())
+}
+
 // TODO Check and evaluate all unwraps
 async fn accept_cookies(driver: &WebDriver) -> anyhow::Result<()> {
     driver
@@ -101,37 +235,6 @@ async fn accept_cookies(driver: &WebDriver) -> anyhow::Result<()> {
     Ok(())
 }
 
-async fn parse_data(driver: &WebDriver) -> anyhow::Result<Vec<Company>> {
-    let source = driver.source().await?;
-    let document = scraper::Html::parse_document(&source);
-    let symbol_selector = scraper::Selector::parse(SYMBOL_SELECTOR)
-        .map_err(|e| eprintln!("{e}"))
-        .unwrap();
-    let names_selector = scraper::Selector::parse(COMPANY_NAME_SELECTOR)
-        .map_err(|e| eprintln!("{e}"))
-        .unwrap();
-    let symbols: Vec<String> = document
-        .select(&symbol_selector)
-        .map(|e| e.inner_html())
-        .collect();
-    let names: Vec<String> = document
-        .select(&names_selector)
-        .map(|e| e.inner_html())
-        .collect();
-    assert_eq!(symbols.len(), names.len());
-
-    let companies: Vec<Company> = symbols
-        .iter()
-        .zip(names.iter())
-        .map(|(s, n)| Company {
-            symbol: s.clone(),
-            name: n.clone(),
-        })
-        .collect();
-
-    Ok(companies)
-}
-
 //async fn close_popup(driver: &WebDriver) -> anyhow::Result<()> {
 //    driver
 //        .query(By::Css(POPUP_CLOSE_BUTTON_SELECTOR))
@@ -143,3 +246,133 @@ async fn parse_data(driver: &WebDriver) -> anyhow::Result<Vec<Company>> {
 //        .await?;
 //    Ok(())
 //}
+
+/// Requests the calendar rows of the `date` from the calendar service
+/// at `url`, batch by batch like the page does while being scrolled,
+/// and wraps them into a table the page selectors can extract.
+async fn fetch_calendar(
+    client: &Client,
+    url: &str,
+    date: NaiveDate,
+) -> anyhow::Result<String> {
+    let date = date.format("%Y-%m-%d").to_string();
+    let mut table = String::from(r#"<table id="earningsCalendarData"><tbody>"#);
+    for batch in 0..MAX_BATCHES {
+        let batch = batch.to_string();
+        let form = [
+            ("country[]", US_COUNTRY_ID),
+            ("dateFrom", &date),
+            ("dateTo", &date),
+            ("currentTab", "custom"),
+            ("limit_from", &batch),
+        ];
+        let calendar: CalendarData = client
+            .post(url)
+            .header("X-Requested-With", "XMLHttpRequest")
+            .header(reqwest::header::REFERER, INVESTING)
+            .form(&form)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        table.push_str(&calendar.data);
+        if !calendar.bind_scroll_handler || calendar.data.trim().is_empty() {
+            table.push_str("</tbody></table>");
+            return Ok(table);
+        }
+    }
+    anyhow::bail!(
+        "The calendar of {date} has more than {MAX_BATCHES} batches of rows"
+    )
+}
+
+#[test]
+fn extract_fixture() {
+    let html = include_str!("fixtures/investing.html");
+    let date = NaiveDate::from_ymd_opt(2023, 4, 18).unwrap();
+    let events = InvestingParser::extract(html, date).unwrap();
+    assert_eq!(
+        events
+            .iter()
+            .map(|e| (e.company.symbol.as_str(), e.company.name.as_str()))
+            .collect::<Vec<_>>(),
+        [
+            ("JNJ", "Johnson and Johnson"),
+            ("NFLX", "Netflix"),
+            ("PNPN", "Power Nickel"),
+        ]
+    );
+    let jnj = &events[0];
+    assert_eq!(jnj.timing, ReportTiming::BeforeOpen);
+    assert_eq!(jnj.eps_estimate.as_deref(), Some("2.5"));
+    assert_eq!(jnj.eps_reported, None);
+    assert_eq!(jnj.revenue_estimate.as_deref(), Some("23.66B"));
+    assert_eq!(jnj.market_cap.as_deref(), Some("433.19B"));
+}
+
+#[tokio::test]
+async fn fetch_calendar_batches() {
+    use wiremock::{
+        matchers::{body_string_contains, header, method},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    let row = |symbol: &str, name: &str| {
+        format!(
+            "<tr><td class=\"flag\"></td><td class=\"left noWrap \
+            earnCalCompany\"><span class=\"earnCalCompanyName \
+            middle\">{name}</span>&nbsp;(<a class=\"bold middle\">\
+            {symbol}</a>)</td><td class=\"eps_actual\">1.1</td></tr>"
+        )
+    };
+    let server = MockServer::start().await;
+    let batches = [
+        (0, row("JNJ", "Johnson and Johnson"), true),
+        (1, row("NFLX", "Netflix"), false),
+    ];
+    for (batch, data, more) in batches {
+        Mock::given(method("POST"))
+            .and(header("X-Requested-With", "XMLHttpRequest"))
+            .and(body_string_contains("dateFrom=2023-04-18"))
+            .and(body_string_contains(format!("limit_from={batch}")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(
+                serde_json::json!({"data": data, "bind_scroll_handler": more}),
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+
+    let client = Client::new();
+    let date = NaiveDate::from_ymd_opt(2023, 4, 18).unwrap();
+    let html = fetch_calendar(&client, &server.uri(), date).await.unwrap();
+    let events = InvestingParser::extract(&html, date).unwrap();
+    assert_eq!(
+        events
+            .iter()
+            .map(|e| (e.company.symbol.as_str(), e.company.name.as_str()))
+            .collect::<Vec<_>>(),
+        [("JNJ", "Johnson and Johnson"), ("NFLX", "Netflix")]
+    );
+    assert_eq!(events[0].eps_reported.as_deref(), Some("1.1"));
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(403))
+        .mount(&server)
+        .await;
+    assert!(fetch_calendar(&client, &server.uri(), date).await.is_err());
+
+    // The rows left after the last batch mustn't be dropped silently.
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(
+            serde_json::json!({"data": row("JNJ", "Johnson and Johnson"),
+                "bind_scroll_handler": true}),
+        ))
+        .expect(MAX_BATCHES as u64)
+        .mount(&server)
+        .await;
+    assert!(fetch_calendar(&client, &server.uri(), date).await.is_err());
+}
@@ -0,0 +1,154 @@
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    process::Stdio,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use tokio::{
+    io::AsyncWriteExt,
+    net::TcpStream,
+    process::{Child, Command},
+};
+
+use super::Browser;
+
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+const READY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// WebDriver process (chromedriver or geckodriver) started by
+/// the program on a free local port. The process is killed when
+/// dropped, [`LocalDriver::stop()`] shuts it down gracefully.
+pub struct LocalDriver {
+    browser: Browser,
+    process: Child,
+    port: u16,
+}
+
+impl LocalDriver {
+    /// Starts the driver binary of the `browser` at `path`, or the one
+    /// found in `PATH`, and waits until it accepts connections.
+    pub async fn start(
+        browser: Browser,
+        path: Option<&Path>,
+    ) -> anyhow::Result<Self> {
+        let name = browser.driver_name();
+        let path = match path {
+            Some(path) => path.to_owned(),
+            None => std::env::var_os("PATH")
+                .and_then(|paths| find_in_path(name, &paths))
+                .with_context(|| format!("Couldn't find {name} in PATH"))?,
+        };
+        let port = free_port()?;
+        let process = Command::new(&path)
+            .arg(format!("--port={port}"))
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| {
+                format!("Couldn't start {name} '{}'", path.display())
+            })?;
+        let mut driver = Self {
+            browser,
+            process,
+            port,
+        };
+        driver.wait_until_ready().await?;
+        Ok(driver)
+    }
+
+    /// URL of the WebDriver endpoint.
+    pub fn url(&self) -> String {
+        format!("http://localhost:{}", self.port)
+    }
+
+    async fn wait_until_ready(&mut self) -> anyhow::Result<()> {
+        let name = self.browser.driver_name();
+        let deadline = Instant::now() + STARTUP_TIMEOUT;
+        loop {
+            if let Some(status) = self.process.try_wait()? {
+                anyhow::bail!("{name} exited on start: {status}");
+            }
+            if TcpStream::connect(("127.0.0.1", self.port)).await.is_ok() {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                anyhow::bail!(
+                    "{name} didn't start in {} seconds",
+                    STARTUP_TIMEOUT.as_secs()
+                );
+            }
+            tokio::time::sleep(READY_INTERVAL).await;
+        }
+    }
+
+    /// Asks chromedriver to shut down and kills it if it doesn't exit
+    /// in time. Geckodriver has no shutdown command, it's killed right
+    /// away and closes its browser itself once the session is quit.
+    pub async fn stop(mut self) -> anyhow::Result<()> {
+        // Killing chromedriver right away could leave its browsers open.
+        if self.browser == Browser::Chrome
+            && self.request_shutdown().await.is_ok()
+        {
+            let exit = self.process.wait();
+            if tokio::time::timeout(SHUTDOWN_TIMEOUT, exit).await.is_ok() {
+                return Ok(());
+            }
+        }
+        self.process.kill().await?;
+        Ok(())
+    }
+
+    async fn request_shutdown(&self) -> std::io::Result<()> {
+        let mut stream = TcpStream::connect(("127.0.0.1", self.port)).await?;
+        let request = format!(
+            "GET /shutdown HTTP/1.1\r\nHost: localhost:{}\r\n\
+            Connection: close\r\n\r\n",
+            self.port
+        );
+        stream.write_all(request.as_bytes()).await
+    }
+}
+
+/// Returns a local port which isn't used at the moment.
+fn free_port() -> std::io::Result<u16> {
+    let listener = std::net::TcpListener::bind(("127.0.0.1", 0))?;
+    Ok(listener.local_addr()?.port())
+}
+
+/// Finds the executable `name` in the directories of the `paths`
+/// list, formatted like the `PATH` variable.
+fn find_in_path(name: &str, paths: &OsStr) -> Option<PathBuf> {
+    let name = if cfg!(windows) {
+        format!("{name}.exe")
+    } else {
+        name.to_owned()
+    };
+    std::env::split_paths(paths)
+        .map(|dir| dir.join(&name))
+        .find(|path| path.is_file())
+}
+
+#[test]
+fn find_executable_in_path() {
+    let root = std::env::temp_dir()
+        .join(format!("earnings-helper-path-{}", std::process::id()));
+    let (empty, bin) = (root.join("empty"), root.join("bin"));
+    std::fs::create_dir_all(&empty).unwrap();
+    std::fs::create_dir_all(&bin).unwrap();
+    let executable = bin.join(if cfg!(windows) {
+        "chromedriver.exe"
+    } else {
+        "chromedriver"
+    });
+    std::fs::write(&executable, "").unwrap();
+
+    let paths = std::env::join_paths([&empty, &bin]).unwrap();
+    assert_eq!(find_in_path("chromedriver", &paths), Some(executable));
+    let paths = std::env::join_paths([&empty]).unwrap();
+    assert_eq!(find_in_path("chromedriver", &paths), None);
+    std::fs::remove_dir_all(root).unwrap();
+}
@@ -1,30 +1,25 @@
-use std::path::Path;
-
 use async_trait::async_trait;
-use chrono::{Datelike, NaiveDate, Weekday};
+use chrono::{Datelike, Days, NaiveDate};
 use thirtyfour::{prelude::ElementQueryable, By, WebDriver};
 
 use super::{
-    Company, WebsiteParser, MARKETWATCH, TIMEOUT_FIVE_SEC, WAIT_INTERVAL,
+    event::select_text, selector, Company, EarningsEvent, Navigation,
+    WebsiteParser, MARKETWATCH, SCROLL_INTO_VIEW, TIMEOUT_FIVE_SEC,
+    WAIT_INTERVAL,
 };
-use crate::{parser::TIMEOUT_TEN_SEC, RelativeDay};
+use crate::parser::TIMEOUT_TEN_SEC;
 
-const SYMBOL_SELECTOR: &str =
-    "div>div>table>tbody>tr>td[class=\"overflow__cell align--left\"]>div>a";
+const ROW_SELECTOR: &str = "div>div>table>tbody>tr";
+const SYMBOL_SELECTOR: &str = "td[class=\"overflow__cell align--left\"]>div>a";
 const COMPANY_NAME_SELECTOR: &str =
-    "div>div>table>tbody>tr>td[class=\"overflow__cell fixed--column align--left\"]>div[class=\"cell__content fixed--cell\"]>a";
+    "td[class=\"overflow__cell fixed--column align--left\"]>div[class=\"cell__content fixed--cell\"]>a";
+const FISCAL_QUARTER_SELECTOR: &str = "td:nth-child(3)";
+const EPS_FORECAST_SELECTOR: &str = "td:nth-child(4)";
 const PREVIOUS_WEEK_SELECTOR: &str = "li[class=\"tab__item prev week\"]";
 const NEXT_WEEK_SELECTOR: &str = "li[class=\"tab__item next week\"]";
-const PREVIOUS_DAY_SELECTOR: &str = "li[class=\"tab__item prev day\"]";
-const NEXT_DAY_SELECTOR: &str = "li[class=\"tab__item next day\"]";
 const COOKIES_AGREE_BUTTON_SELECTOR: &str = "button[class=\"message-component message-button no-children focusable agree-btn sp_choice_type_11\"]";
-const COOKIE_MESSAGE_IFRAME: &str = "sp_message_iframe_719544";
-const COOKIE_MESSAGE_IFRAME_SELECTOR: &str = "iframe[title=\"SP Consent Message\"]";
-
-// Weird website logic bug when today is monday and the 'previous day'
-// button is pressed. It move the calendar back 2 weeks instead of 1.
-// Will not be fixed because Sunday's usually don't have scheduled
-// earnings releases.
+const COOKIE_MESSAGE_IFRAME_SELECTOR: &str =
+    "iframe[title=\"SP Consent Message\"]";
 
 pub struct MarketWatchParser {}
 
@@ -32,75 +27,136 @@ pub struct MarketWatchParser {}
 impl WebsiteParser for MarketWatchParser {
     const NAME: &'static str = "MarketWatch";
 
-    async fn parse(
+    async fn navigate(
         driver: &WebDriver,
-        day: RelativeDay,
-    ) -> anyhow::Result<Vec<Company>> {
+        date: NaiveDate,
+    ) -> anyhow::Result<Navigation> {
         driver.goto(MARKETWATCH).await?;
         // If cookies window was not found then make sure to return
         // back to the default frame.
         accept_cookies(driver)
             .await
             .or(driver.enter_default_frame().await)?;
-        
+
         let today = chrono::offset::Local::now().date_naive();
-        let target = day.get_date();
-        let target_weekday = target.weekday();
-        // Weekdays here are counted from Mon to Sun.
-        match today.weekday() {
-            // Check if the target week is before the today week
-            Weekday::Mon => {
-                if target_weekday == Weekday::Sun {
-                    to_previous_week(driver).await?;
-                }
+        // Move the calendar from the today week to the target week.
+        let weeks = (week_start(date) - week_start(today)).num_weeks();
+        let mut shown = week_start(today);
+        for _ in 0..weeks.abs() {
+            if weeks < 0 {
+                to_previous_week(driver).await?;
+                shown = shown.checked_sub_days(Days::new(7)).unwrap();
+            } else {
+                to_next_week(driver).await?;
+                shown = shown.checked_add_days(Days::new(7)).unwrap();
             }
-            // Check if the target week is after the today week
-            Weekday::Sun => {
-                if target_weekday == Weekday::Mon {
-                    to_next_week(driver).await?;
-                }
-            }
-            _ => (),
+            driver
+                .query(By::Css(date_pane_selector(shown)))
+                .wait(TIMEOUT_TEN_SEC, WAIT_INTERVAL)
+                .desc("Wait for the week data to load")
+                .single()
+                .await?;
         }
-        parse_data(driver, target).await
+        driver
+            .query(By::Css(date_pane_selector(date)))
+            .wait(TIMEOUT_TEN_SEC, WAIT_INTERVAL)
+            .desc("Find the current date data")
+            .single()
+            .await?;
+        Ok(Navigation::Loaded)
+    }
+
+    async fn load_week(
+        driver: &WebDriver,
+        dates: &[NaiveDate],
+    ) -> anyhow::Result<Vec<Option<String>>> {
+        let Some(&first) = dates.first() else {
+            return Ok(Vec::new());
+        };
+        // Every day of the week has its own pane on the same page.
+        Self::navigate(driver, first).await?;
+        let source = driver.source().await?;
+        Ok(vec![Some(source); dates.len()])
+    }
+
+    fn extract(
+        html: &str,
+        date: NaiveDate,
+    ) -> anyhow::Result<Vec<EarningsEvent>> {
+        let document = scraper::Html::parse_document(html);
+        let row_selector = selector(&format!(
+            "{}>{}",
+            date_pane_selector(date),
+            ROW_SELECTOR
+        ))?;
+        let symbol_selector = selector(SYMBOL_SELECTOR)?;
+        let company_name_selector = selector(COMPANY_NAME_SELECTOR)?;
+        let fiscal_quarter_selector = selector(FISCAL_QUARTER_SELECTOR)?;
+        let eps_forecast_selector = selector(EPS_FORECAST_SELECTOR)?;
+
+        let mut events = Vec::new();
+        for row in document.select(&row_selector) {
+            let Some(symbol) = row.select(&symbol_selector).next() else {
+                continue;
+            };
+            let symbol = symbol.inner_html();
+            let name = row
+                .select(&company_name_selector)
+                .next()
+                .map(|e| e.inner_html())
+                .ok_or_else(|| {
+                    anyhow::anyhow!("Found no company name for '{symbol}'")
+                })?;
+
+            let mut event =
+                EarningsEvent::new(Self::NAME, Company { symbol, name });
+            event.fiscal_period = select_text(row, &fiscal_quarter_selector);
+            event.eps_estimate = select_text(row, &eps_forecast_selector);
+            events.push(event);
+        }
+
+        Ok(events)
     }
 }
 
+/// Each date has its own pane with the earnings table.
+fn date_pane_selector(date: NaiveDate) -> String {
+    format!("div.element[data-tab-pane=\"{}\"]", date.format("%m/%d/%Y"))
+}
+
+/// Weeks here are counted from Mon to Sun.
+fn week_start(date: NaiveDate) -> NaiveDate {
+    let weekday_num = date.weekday().num_days_from_monday() as u64;
+    date.checked_sub_days(Days::new(weekday_num)).unwrap()
+}
+
+// The buttons are scrolled to the middle of the window since Firefox
+// scrolls them to the bottom edge, where the ads banner covers them.
 async fn to_previous_week(driver: &WebDriver) -> anyhow::Result<()> {
-    // Firstly, click the previous day button if available
-    if driver
-        .find(By::Css(PREVIOUS_DAY_SELECTOR))
-        .await?
-        .click()
-        .await
-        .is_err()
-    {
-        // If it was not available, click the previous week button.
-        driver
-            .find(By::Css(PREVIOUS_WEEK_SELECTOR))
-            .await?
-            .click()
-            .await?;
-    }
+    let button = driver
+        .query(By::Css(PREVIOUS_WEEK_SELECTOR))
+        .wait(TIMEOUT_FIVE_SEC, WAIT_INTERVAL)
+        .desc("Find 'Previous Week' button")
+        .single()
+        .await?;
+    driver
+        .execute(SCROLL_INTO_VIEW, vec![button.to_json()?])
+        .await?;
+    button.click().await?;
     Ok(())
 }
 
 async fn to_next_week(driver: &WebDriver) -> anyhow::Result<()> {
-    // Firstly, click the next day button if available
-    if driver
-        .find(By::Css(NEXT_DAY_SELECTOR))
-        .await?
-        .click()
-        .await
-        .is_err()
-    {
-        // If it was not available, click the next week button.
-        driver
-            .find(By::Css(NEXT_WEEK_SELECTOR))
-            .await?
-            .click()
-            .await?;
-    }
+    let button = driver
+        .query(By::Css(NEXT_WEEK_SELECTOR))
+        .wait(TIMEOUT_FIVE_SEC, WAIT_INTERVAL)
+        .desc("Find 'Next Week' button")
+        .single()
+        .await?;
+    driver
+        .execute(SCROLL_INTO_VIEW, vec![button.to_json()?])
+        .await?;
+    button.click().await?;
     Ok(())
 }
 
@@ -124,50 +180,42 @@ async fn accept_cookies(driver: &WebDriver) -> anyhow::Result<()> {
     Ok(())
 }
 
-async fn parse_data(
-    driver: &WebDriver,
-    date: NaiveDate,
-) -> anyhow::Result<Vec<Company>> {
-    let date_selector =
-        &format!("div.element[data-tab-pane=\"{}\"]", date.format("%m/%d/%Y"));
-    driver
-        .query(By::Css(date_selector))
-        .wait(TIMEOUT_TEN_SEC, WAIT_INTERVAL)
-        .desc("Find the current date data")
-        .single()
-        .await?;
-    let source = driver.source().await?;
-    std::fs::write("./source.html", &source)?;
-    let document = scraper::Html::parse_document(&source);
-
-    let css_symbol_selector = &format!("{}>{}", date_selector, SYMBOL_SELECTOR);
-    let css_company_name_selector =
-        &format!("{}>{}", date_selector, COMPANY_NAME_SELECTOR);
-    let symbol_selector = scraper::Selector::parse(css_symbol_selector)
-        .map_err(|e| eprintln!("{e}"))
-        .unwrap();
-    let company_name_selector =
-        scraper::Selector::parse(css_company_name_selector)
-            .map_err(|e| eprintln!("{e}"))
-            .unwrap();
-
-    let symbols: Vec<String> = document
-        .select(&symbol_selector)
-        .map(|e| e.inner_html())
-        .collect();
-    let company_names: Vec<String> = document
-        .select(&company_name_selector)
-        .map(|e| e.inner_html())
-        .collect();
-    assert_eq!(symbols.len(), company_names.len());
-    let companies: Vec<Company> = symbols
-        .iter()
-        .zip(company_names.iter())
-        .map(|(symbol, name)| Company {
-            symbol: symbol.clone(),
-            name: name.clone(),
-        })
-        .collect();
-
-    Ok(companies)
+#[test]
+fn extract_fixture() {
+    let html = include_str!("fixtures/marketwatch.html");
+    let date = NaiveDate::from_ymd_opt(2024, 6, 7).unwrap();
+    let events = MarketWatchParser::extract(html, date).unwrap();
+    assert_eq!(
+        events
+            .iter()
+            .map(|e| (e.company.symbol.as_str(), e.company.name.as_str()))
+            .collect::<Vec<_>>(),
+        [
+            ("GME", "GameStop Corp. Cl A"),
+            ("GHM", "Graham Corp."),
+            ("JILL", "J.Jill Inc."),
+            ("VIRC", "Virco Manufacturing Corp."),
+        ]
+    );
+    let gme = &events[0];
+    assert_eq!(gme.fiscal_period.as_deref(), Some("04/30/2024"));
+    assert_eq!(gme.eps_estimate.as_deref(), Some("-0.09"));
+}
+
+#[test]
+fn extract_other_date_panes() {
+    let html = include_str!("fixtures/marketwatch.html");
+    let date = NaiveDate::from_ymd_opt(2024, 6, 3).unwrap();
+    let events = MarketWatchParser::extract(html, date).unwrap();
+    let symbols: Vec<&str> =
+        events.iter().map(|e| e.company.symbol.as_str()).collect();
+    assert_eq!(symbols, ["SAIC", "EM", "PNPNF"]);
+
+    // The pane only contains a 'no earnings scheduled' message.
+    let date = NaiveDate::from_ymd_opt(2024, 6, 9).unwrap();
+    assert!(MarketWatchParser::extract(html, date).unwrap().is_empty());
+
+    // The page doesn't hold a pane for the date.
+    let date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+    assert!(MarketWatchParser::extract(html, date).unwrap().is_empty());
 }
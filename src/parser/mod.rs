@@ -1,14 +1,29 @@
 mod benzinga_parser;
+mod browser;
+mod capture;
+mod event;
+mod http;
 mod investing_parser;
+mod local_driver;
 mod marketwatch_parser;
 mod tradingview_parser;
 mod zacks_parser;
 
+use anyhow::Context;
 use async_trait::async_trait;
-use std::{io::Write, time::Duration};
+use std::{io::Write, path::Path, time::Duration};
 
-use chrono::{Days, NaiveDate};
-use thirtyfour::{ChromiumLikeCapabilities, DesiredCapabilities, WebDriver};
+use chrono::{Datelike, NaiveDate};
+use reqwest::Client;
+use thirtyfour::{Capabilities, WebDriver};
+
+pub use self::{
+    browser::{capabilities, Browser},
+    capture::Capture,
+    event::{EarningsEvent, ReportTiming},
+    http::http_client,
+    local_driver::LocalDriver,
+};
 
 use self::{
     benzinga_parser::BenzingaParser, investing_parser::InvestingParser,
@@ -20,6 +35,8 @@ const MARKETWATCH: &str = "https://www.marketwatch.com/tools/earnings-calendar";
 const ZACKS: &str = "https://www.zacks.com/earnings/earnings-calendar?icid=earnings-earnings-nav_tracking-zcom-main_menu_wrapper-earnings_calendar";
 const BENZINGA: &str = "https://www.benzinga.com/calendars/earnings";
 const INVESTING: &str = "https://www.investing.com/earnings-calendar/";
+const INVESTING_CALENDAR: &str =
+    "https://www.investing.com/earnings-calendar/Service/getCalendarFilteredData";
 // const EARNINGSWHISPERS: &str = "https://www.earningswhispers.com/calendar";
 const TRADINGVIEW: &str =
     "https://www.tradingview.com/markets/stocks-usa/earnings/";
@@ -32,82 +49,394 @@ const LOAD_WAIT: Duration = Duration::from_secs(2);
 const TIMEOUT_FIVE_SEC: Duration = Duration::from_secs(5);
 const TIMEOUT_TEN_SEC: Duration = Duration::from_secs(10);
 const MAX_RERUNS: usize = 1;
+const WEB_PARSERS: [Parser; 5] = [
+    Parser::Marketwatch,
+    Parser::Zacks,
+    Parser::Tradingview,
+    Parser::Investing,
+    Parser::Benzinga,
+];
+
+/// All earnings events parsed for one target date.
+pub struct DayData {
+    pub date: NaiveDate,
+    pub events: Vec<EarningsEvent>,
+    /// Names of the websites which were parsed for the date.
+    pub attempted: Vec<&'static str>,
+    /// Names of the websites which were parsed successfully.
+    pub succeeded: Vec<&'static str>,
+}
 
-/// Returns all parsed data in one `Vec` with an average of entries
-/// per parsed website.
+/// Returns all parsed data for each of the `dates`, with the websites
+/// which were parsed for each date.
+///
+/// If `whole_weeks` is set, dates from the same week are parsed
+/// together so websites showing the whole week on one page
+/// are only navigated once.
+///
+/// A browser session requested with the `caps` is opened at each of
+/// the WebDriver `endpoints` (which may repeat), so that many websites
+/// are parsed at the same time. Websites which don't need a browser
+/// are fetched with the `client`. Without any `endpoints` only they
+/// are parsed.
+///
+/// If `capture` is provided, the final page of every website is saved
+/// into its run directory.
 pub async fn parse_website_data(
-    day: RelativeDay,
-    window_is_visible: bool,
-) -> anyhow::Result<(Vec<Company>, usize)> {
-    let mut stdout = std::io::stdout().lock();
-    write!(stdout, "Initializing WebDriver...")?;
-    stdout.flush()?;
+    dates: &[NaiveDate],
+    whole_weeks: bool,
+    endpoints: &[String],
+    caps: Capabilities,
+    client: Client,
+    mut capture: Option<Capture>,
+) -> anyhow::Result<Vec<DayData>> {
+    let mut stderr = std::io::stderr().lock();
+    let drivers = if endpoints.is_empty() {
+        writeln!(stderr, "Parsing only the websites not needing a browser.")?;
+        Vec::new()
+    } else {
+        start_sessions(&mut stderr, endpoints, caps).await?
+    };
+    let web_parsers: Vec<Parser> = WEB_PARSERS
+        .into_iter()
+        .filter(|p| !drivers.is_empty() || p.backend() == Backend::Http)
+        .collect();
 
-    let mut caps = DesiredCapabilities::chrome();
-    if !window_is_visible {
-        caps.set_headless()?;
+    let groups: Vec<&[NaiveDate]> = if whole_weeks {
+        dates
+            .chunk_by(|a, b| a.iso_week() == b.iso_week())
+            .collect()
+    } else {
+        dates.chunks(1).collect()
+    };
+    let mut result = Vec::with_capacity(dates.len());
+    for group in groups {
+        match group {
+            [date] => writeln!(stderr, "Parsing data for {date}:")?,
+            [first, .., last] => {
+                writeln!(stderr, "Parsing data for {first} - {last}:")?
+            }
+            [] => continue,
+        }
+        let parsed = parse_all(
+            &drivers,
+            &client,
+            group,
+            web_parsers.clone(),
+            capture.as_mut(),
+        )
+        .await?;
+        result.extend(parsed);
     }
-    let driver = WebDriver::new("http://localhost:9515", caps)
-        .await
-        .map_err(|e| writeln!(stdout, "Is chromedriver started? Error: {e}"))
-        .unwrap();
-    writeln!(stdout, "Success!")?;
 
-    let web_parsers = vec![
-        Parser::Marketwatch,
-        Parser::Zacks,
-        Parser::Tradingview,
-        Parser::Investing,
-        Parser::Benzinga,
-    ];
-    let parsed = parse_all(&driver, day, web_parsers).await?;
-
-    driver.quit().await?;
+    for driver in drivers {
+        driver.quit().await?;
+    }
 
-    let parsed_websites = parsed.len();
-    // Store all data into one big array.
-    let data: Vec<Company> = parsed.into_iter().flatten().collect();
+    if let Some(capture) = capture {
+        let manifest = capture.write_manifest()?;
+        writeln!(stderr, "Captured pages saved at: '{}'", manifest.display())?;
+    }
 
-    Ok((data, parsed_websites))
+    Ok(result)
 }
 
-async fn parse_all(
-    driver: &WebDriver,
-    day: RelativeDay,
-    web_parsers: Vec<Parser>,
-) -> anyhow::Result<Vec<Vec<Company>>> {
-    let mut stdout = std::io::stdout().lock();
-    let mut result = Vec::with_capacity(web_parsers.len());
+/// Opens a browser session requested with the `caps` at each of
+/// the WebDriver `endpoints`.
+async fn start_sessions(
+    stderr: &mut impl Write,
+    endpoints: &[String],
+    caps: Capabilities,
+) -> anyhow::Result<Vec<WebDriver>> {
+    match endpoints.len() {
+        1 => write!(stderr, "Initializing WebDriver...")?,
+        sessions => {
+            write!(stderr, "Initializing {sessions} WebDriver sessions...")?
+        }
+    }
+    stderr.flush()?;
 
-    for parser in web_parsers {
-        write!(stdout, "Reading '{}' data...", parser.get_name())?;
-        let mut max_reruns = MAX_RERUNS;
+    let drivers = futures::future::try_join_all(
+        endpoints
+            .iter()
+            .map(|url| WebDriver::new(url, caps.clone())),
+    )
+    .await
+    .with_context(|| {
+        format!(
+            "Is the WebDriver at {} started? Start it or \
+            use --start-driver",
+            endpoints[0]
+        )
+    })?;
+    writeln!(stderr, "Success!")?;
+    Ok(drivers)
+}
 
-        loop {
-            stdout.flush()?;
+/// Same as [`parse_website_data()`] but instead of driving a browser
+/// the data is extracted from page snapshots previously saved
+/// in `dir`, one `<website>.html` file per website
+/// (e.g. `marketwatch.html`). Snapshots of each date are looked up
+/// in the `dir/YYYY-MM-DD` directory first and then in `dir` itself.
+/// Websites without a snapshot are skipped. An empty snapshot means
+/// the website had no earnings scheduled for the date.
+pub fn replay_snapshots(
+    dir: &Path,
+    dates: &[NaiveDate],
+) -> anyhow::Result<Vec<DayData>> {
+    let mut stderr = std::io::stderr().lock();
+    let mut result = Vec::with_capacity(dates.len());
 
-            match parser.parse(driver, day).await {
-                Ok(parsed) => {
-                    writeln!(stdout, " Success! Parsed entries: {}", parsed.len())?;
-                    result.push(parsed);
-                    break;
-                }
+    for &date in dates {
+        writeln!(stderr, "Replaying data for {date}:")?;
+        let mut day = DayData {
+            date,
+            events: Vec::new(),
+            attempted: Vec::new(),
+            succeeded: Vec::new(),
+        };
+        for parser in WEB_PARSERS {
+            write!(stderr, "Replaying '{}' data...", parser.get_name())?;
+            let file_name = parser.snapshot_file_name();
+            let mut path = dir.join(Capture::date_dir(date)).join(&file_name);
+            if !path.exists() {
+                path = dir.join(&file_name);
+            }
+            let source = match std::fs::read_to_string(&path) {
+                Ok(source) => source,
                 Err(e) => {
-                    if max_reruns == 0 {
-                        writeln!(stdout, "\nCouldn't parse data: {e}")?;
-                        break;
-                    }
-                    writeln!(stdout, "Failed to parse data: {e}")?;
-                    write!(stdout, "Trying again...")?;
-                    max_reruns -= 1;
+                    writeln!(stderr, " Skipped '{}': {e}", path.display())?;
                     continue;
                 }
+            };
+            day.attempted.push(parser.get_name());
+            let events = if source.trim().is_empty() {
+                Ok(Vec::new())
+            } else {
+                parser.extract(&source, date)
+            };
+            match events {
+                Ok(events) => {
+                    writeln!(
+                        stderr,
+                        " Success! Parsed entries: {}",
+                        events.len()
+                    )?;
+                    day.succeeded.push(parser.get_name());
+                    day.events.extend(events);
+                }
+                Err(e) => writeln!(stderr, "\nCouldn't parse data: {e}")?,
+            }
+        }
+        result.push(day);
+    }
+
+    Ok(result)
+}
+
+/// Parses all `dates` (belonging to the same week) on each website.
+/// Every driver takes the next website to parse until none are left,
+/// so the websites are parsed concurrently when there are multiple
+/// drivers. Without drivers a single worker fetches the websites
+/// using the HTTP backend. Websites are only parsed for the dates they
+/// list. The results keep the order of `web_parsers`.
+async fn parse_all(
+    drivers: &[WebDriver],
+    client: &Client,
+    dates: &[NaiveDate],
+    web_parsers: Vec<Parser>,
+    capture: Option<&mut Capture>,
+) -> anyhow::Result<Vec<DayData>> {
+    let progress = Progress {
+        concurrent: drivers.len() > 1,
+    };
+    let sessions: Vec<Option<&WebDriver>> = if drivers.is_empty() {
+        vec![None]
+    } else {
+        drivers.iter().map(Some).collect()
+    };
+    let queue = std::sync::Mutex::new(web_parsers.into_iter().enumerate());
+    let capture = tokio::sync::Mutex::new(capture);
+    let workers = sessions.iter().map(|driver| async {
+        let driver = *driver;
+        let mut outcomes = Vec::new();
+        loop {
+            let Some((i, parser)) = queue.lock().unwrap().next() else {
+                break;
+            };
+            let listed: Vec<NaiveDate> =
+                dates.iter().copied().filter(|&d| parser.lists(d)).collect();
+            if listed.is_empty() {
+                continue;
+            }
+            let outcome =
+                parse_website(driver, client, parser, &listed, &progress)
+                    .await?;
+            // Pages fetched over HTTP weren't shown by the driver, unless
+            // fetching failed and they were loaded in the browser instead.
+            let driver = driver.filter(|_| {
+                parser.backend() == Backend::WebDriver || outcome.is_err()
+            });
+            if let Some(capture) = capture.lock().await.as_deref_mut() {
+                capture_pages(capture, driver, parser, &listed, &outcome)
+                    .await?;
+            }
+            outcomes.push((i, parser, listed, outcome));
+        }
+        anyhow::Ok(outcomes)
+    });
+    let mut outcomes: Vec<_> = futures::future::try_join_all(workers)
+        .await?
+        .into_iter()
+        .flatten()
+        .collect();
+    outcomes.sort_by_key(|(i, _, _, _)| *i);
+
+    let mut result: Vec<DayData> = dates
+        .iter()
+        .map(|&date| DayData {
+            date,
+            events: Vec::new(),
+            attempted: Vec::new(),
+            succeeded: Vec::new(),
+        })
+        .collect();
+    for (_, parser, listed, outcome) in outcomes {
+        let days = result.iter_mut().filter(|day| listed.contains(&day.date));
+        match outcome {
+            Ok(parsed) => {
+                for (day, page) in days.zip(parsed) {
+                    day.attempted.push(parser.get_name());
+                    day.succeeded.push(parser.get_name());
+                    day.events.extend(page.events);
+                }
+            }
+            Err(_) => {
+                days.for_each(|day| day.attempted.push(parser.get_name()))
             }
         }
     }
     Ok(result)
 }
 
+/// Parses all `dates` on the website, trying again after a failure
+/// up to [`MAX_RERUNS`] times.
+async fn parse_website(
+    driver: Option<&WebDriver>,
+    client: &Client,
+    parser: Parser,
+    dates: &[NaiveDate],
+    progress: &Progress,
+) -> anyhow::Result<Result<Vec<ParsedPage>, String>> {
+    let name = parser.get_name();
+    progress.start(name)?;
+    let mut max_reruns = MAX_RERUNS;
+    loop {
+        match parser.parse(driver, client, dates, progress).await {
+            Ok(parsed) => {
+                let entries: usize =
+                    parsed.iter().map(|p| p.events.len()).sum();
+                progress.report(
+                    name,
+                    &format!("Success! Parsed entries: {entries}"),
+                )?;
+                return Ok(Ok(parsed));
+            }
+            Err(e) => {
+                if max_reruns == 0 {
+                    progress
+                        .report(name, &format!("Couldn't parse data: {e}"))?;
+                    return Ok(Err(e.to_string()));
+                }
+                progress.report(name, &format!("Failed to parse data: {e}"))?;
+                progress.retry(name)?;
+                max_reruns -= 1;
+            }
+        }
+    }
+}
+
+/// Saves the pages loaded by the website for each of the `dates`,
+/// an empty page for the dates without earnings, or the page shown
+/// by the `driver` if parsing failed.
+async fn capture_pages(
+    capture: &mut Capture,
+    driver: Option<&WebDriver>,
+    parser: Parser,
+    dates: &[NaiveDate],
+    outcome: &Result<Vec<ParsedPage>, String>,
+) -> anyhow::Result<()> {
+    let mut stderr = std::io::stderr().lock();
+    for (i, &date) in dates.iter().enumerate() {
+        let (source, outcome) = match outcome {
+            // The driver shows the page of another date when the website
+            // has no earnings for this one, so an empty snapshot marks it.
+            Ok(parsed) => {
+                let page = &parsed[i];
+                (
+                    Some(page.source.as_deref().unwrap_or("")),
+                    Ok(page.events.len()),
+                )
+            }
+            Err(e) => (None, Err(e.clone())),
+        };
+        if let Err(e) =
+            capture.save(driver, parser, date, source, outcome).await
+        {
+            writeln!(stderr, "Couldn't capture the page: {e}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Prints the parsing progress of each website. When websites are
+/// parsed concurrently every message is a whole line naming
+/// the website, otherwise the outcome follows on the same line.
+struct Progress {
+    concurrent: bool,
+}
+
+impl Progress {
+    fn start(&self, name: &str) -> std::io::Result<()> {
+        let mut stderr = std::io::stderr().lock();
+        write!(stderr, "Reading '{name}' data...")?;
+        if self.concurrent {
+            writeln!(stderr)?;
+        }
+        stderr.flush()
+    }
+
+    fn retry(&self, name: &str) -> std::io::Result<()> {
+        let mut stderr = std::io::stderr().lock();
+        if self.concurrent {
+            writeln!(stderr, "'{name}': Trying again...")?;
+        } else {
+            write!(stderr, "Trying again...")?;
+        }
+        stderr.flush()
+    }
+
+    fn fallback(&self, name: &str) -> std::io::Result<()> {
+        let mut stderr = std::io::stderr().lock();
+        if self.concurrent {
+            writeln!(stderr, "'{name}': Trying in the browser...")?;
+        } else {
+            write!(stderr, "Trying in the browser...")?;
+        }
+        stderr.flush()
+    }
+
+    fn report(&self, name: &str, message: &str) -> std::io::Result<()> {
+        let mut stderr = std::io::stderr().lock();
+        if self.concurrent {
+            writeln!(stderr, "'{name}': {message}")
+        } else {
+            writeln!(stderr, " {message}")
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
 enum Parser {
     Marketwatch,
     Zacks,
@@ -127,21 +456,141 @@ impl Parser {
         }
     }
 
-    async fn parse(
+    fn backend(&self) -> Backend {
+        match self {
+            Self::Marketwatch => MarketWatchParser::BACKEND,
+            Self::Zacks => ZacksParser::BACKEND,
+            Self::Tradingview => TradingViewParser::BACKEND,
+            Self::Investing => InvestingParser::BACKEND,
+            Self::Benzinga => BenzingaParser::BACKEND,
+        }
+    }
+
+    fn lists(&self, date: NaiveDate) -> bool {
+        match self {
+            Self::Marketwatch => MarketWatchParser::lists(date),
+            Self::Zacks => ZacksParser::lists(date),
+            Self::Tradingview => TradingViewParser::lists(date),
+            Self::Investing => InvestingParser::lists(date),
+            Self::Benzinga => BenzingaParser::lists(date),
+        }
+    }
+
+    /// Name of the file holding a saved page of the website.
+    fn snapshot_file_name(&self) -> String {
+        format!("{}.html", self.get_name().to_lowercase())
+    }
+
+    async fn load_week(
         &self,
         driver: &WebDriver,
-        day: RelativeDay,
-    ) -> anyhow::Result<Vec<Company>> {
+        dates: &[NaiveDate],
+    ) -> anyhow::Result<Vec<Option<String>>> {
         match self {
-            Self::Marketwatch => MarketWatchParser::parse(driver, day).await,
-            Self::Zacks => ZacksParser::parse(driver, day).await,
-            Self::Tradingview => TradingViewParser::parse(driver, day).await,
-            Self::Investing => InvestingParser::parse(driver, day).await,
-            Self::Benzinga => BenzingaParser::parse(driver, day).await,
+            Self::Marketwatch => {
+                MarketWatchParser::load_week(driver, dates).await
+            }
+            Self::Zacks => ZacksParser::load_week(driver, dates).await,
+            Self::Tradingview => {
+                TradingViewParser::load_week(driver, dates).await
+            }
+            Self::Investing => InvestingParser::load_week(driver, dates).await,
+            Self::Benzinga => BenzingaParser::load_week(driver, dates).await,
+        }
+    }
+
+    async fn fetch(
+        &self,
+        client: &Client,
+        date: NaiveDate,
+    ) -> anyhow::Result<Option<String>> {
+        match self {
+            Self::Marketwatch => MarketWatchParser::fetch(client, date).await,
+            Self::Zacks => ZacksParser::fetch(client, date).await,
+            Self::Tradingview => TradingViewParser::fetch(client, date).await,
+            Self::Investing => InvestingParser::fetch(client, date).await,
+            Self::Benzinga => BenzingaParser::fetch(client, date).await,
+        }
+    }
+
+    async fn fetch_week(
+        &self,
+        client: &Client,
+        dates: &[NaiveDate],
+    ) -> anyhow::Result<Vec<Option<String>>> {
+        let mut pages = Vec::with_capacity(dates.len());
+        for &date in dates {
+            pages.push(self.fetch(client, date).await?);
+        }
+        Ok(pages)
+    }
+
+    fn extract(
+        &self,
+        html: &str,
+        date: NaiveDate,
+    ) -> anyhow::Result<Vec<EarningsEvent>> {
+        match self {
+            Self::Marketwatch => MarketWatchParser::extract(html, date),
+            Self::Zacks => ZacksParser::extract(html, date),
+            Self::Tradingview => TradingViewParser::extract(html, date),
+            Self::Investing => InvestingParser::extract(html, date),
+            Self::Benzinga => BenzingaParser::extract(html, date),
+        }
+    }
+
+    /// Loads the pages of all `dates` with the backend of the website
+    /// and extracts their events. Pages which couldn't be fetched over
+    /// HTTP are loaded with the `driver` instead, if there is one.
+    async fn parse(
+        &self,
+        driver: Option<&WebDriver>,
+        client: &Client,
+        dates: &[NaiveDate],
+        progress: &Progress,
+    ) -> anyhow::Result<Vec<ParsedPage>> {
+        let pages = match (self.backend(), driver) {
+            (Backend::WebDriver, Some(driver)) => {
+                self.load_week(driver, dates).await?
+            }
+            (Backend::WebDriver, None) => {
+                anyhow::bail!("'{}' needs a browser", self.get_name())
+            }
+            (Backend::Http, driver) => {
+                match (self.fetch_week(client, dates).await, driver) {
+                    (Ok(pages), _) => pages,
+                    (Err(e), Some(driver)) => {
+                        let name = self.get_name();
+                        progress.report(
+                            name,
+                            &format!("Failed to fetch data: {e}"),
+                        )?;
+                        progress.fallback(name)?;
+                        self.load_week(driver, dates).await?
+                    }
+                    (Err(e), None) => return Err(e),
+                }
+            }
+        };
+        let mut parsed = Vec::with_capacity(pages.len());
+        for (&date, source) in dates.iter().zip(pages) {
+            let events = match &source {
+                Some(source) => self.extract(source, date)?,
+                None => Vec::new(),
+            };
+            parsed.push(ParsedPage { source, events });
         }
+        Ok(parsed)
     }
 }
 
+/// Events extracted from the page of a single date.
+struct ParsedPage {
+    /// Page source, if the website had earnings scheduled for the date.
+    source: Option<String>,
+    events: Vec<EarningsEvent>,
+}
+
 #[derive(Debug, Clone, Eq)]
 pub struct Company {
     pub symbol: String,
@@ -156,7 +605,7 @@ impl PartialEq for Company {
 
 impl PartialOrd for Company {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.symbol.partial_cmp(&other.symbol)
+        Some(self.cmp(other))
     }
 }
 
@@ -166,35 +615,107 @@ impl Ord for Company {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-pub enum RelativeDay {
-    Yesterday,
-    Today,
-    Tomorrow,
+/// Outcome of navigating a website calendar to the target date.
+enum Navigation {
+    /// The page holds the earnings table for the target date.
+    Loaded,
+    /// The website has no earnings scheduled for the target date.
+    Empty,
 }
 
-impl RelativeDay {
-    pub fn get_date(&self) -> NaiveDate {
-        use chrono::offset::Local;
-        let now = Local::now();
-        match self {
-            RelativeDay::Yesterday => {
-                now.checked_sub_days(Days::new(1)).unwrap().date_naive()
-            }
-            RelativeDay::Today => now.date_naive(),
-            RelativeDay::Tomorrow => {
-                now.checked_add_days(Days::new(1)).unwrap().date_naive()
-            }
-        }
-    }
+/// How the pages of a website are loaded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Backend {
+    /// Navigated in a browser session through the WebDriver.
+    WebDriver,
+    /// Fetched with plain HTTP requests, for websites which don't need
+    /// JavaScript to show their calendar.
+    Http,
 }
 
 #[async_trait]
 trait WebsiteParser {
     const NAME: &'static str;
+    const BACKEND: Backend = Backend::WebDriver;
 
-    async fn parse(
+    /// Whether the calendar of the website lists the `date` at all.
+    /// Dates it doesn't list are skipped instead of parsed.
+    fn lists(_date: NaiveDate) -> bool {
+        true
+    }
+
+    /// Opens the website and navigates its calendar to the target `date`,
+    /// which websites using the [`Backend::WebDriver`] backend must
+    /// implement. Websites using the [`Backend::Http`] backend can
+    /// implement it to be loaded in the browser when fetching fails.
+    async fn navigate(
+        _driver: &WebDriver,
+        _date: NaiveDate,
+    ) -> anyhow::Result<Navigation> {
+        anyhow::bail!("'{}' isn't parsed in a browser", Self::NAME)
+    }
+
+    /// Returns the page source for each of the `dates`, which all
+    /// belong to the same week. `None` means the website has no
+    /// earnings scheduled for the date.
+    ///
+    /// By default the calendar is navigated separately for every date.
+    /// Websites showing the whole week on one page should override it.
+    async fn load_week(
         driver: &WebDriver,
-        day: RelativeDay,
-    ) -> anyhow::Result<Vec<Company>>;
+        dates: &[NaiveDate],
+    ) -> anyhow::Result<Vec<Option<String>>> {
+        let mut pages = Vec::with_capacity(dates.len());
+        for &date in dates {
+            let page = match Self::navigate(driver, date).await? {
+                Navigation::Loaded => Some(driver.source().await?),
+                Navigation::Empty => None,
+            };
+            pages.push(page);
+        }
+        Ok(pages)
+    }
+
+    /// Fetches the page of the target `date` without a browser, which
+    /// websites using the [`Backend::Http`] backend must implement.
+    /// `None` means the website has no earnings scheduled for the date.
+    async fn fetch(
+        _client: &Client,
+        _date: NaiveDate,
+    ) -> anyhow::Result<Option<String>> {
+        anyhow::bail!("'{}' can't be fetched without a browser", Self::NAME)
+    }
+
+    /// Extracts all earnings events scheduled for `date` from
+    /// the page source.
+    ///
+    /// Doesn't need a running WebDriver so it can also be used
+    /// on previously saved pages.
+    fn extract(
+        html: &str,
+        date: NaiveDate,
+    ) -> anyhow::Result<Vec<EarningsEvent>>;
+}
+
+/// Parses the CSS `selector`, turning the parse error into
+/// an [`anyhow::Error`].
+fn selector(selector: &str) -> anyhow::Result<scraper::Selector> {
+    scraper::Selector::parse(selector)
+        .map_err(|e| anyhow::anyhow!("Invalid selector '{selector}': {e}"))
+}
+
+#[test]
+fn replay_empty_snapshot() {
+    let dir = std::env::temp_dir()
+        .join(format!("earnings-helper-replay-{}", std::process::id()));
+    let date = NaiveDate::from_ymd_opt(2023, 4, 18).unwrap();
+    let date_dir = dir.join(Capture::date_dir(date));
+    std::fs::create_dir_all(&date_dir).unwrap();
+    std::fs::write(date_dir.join("zacks.html"), "").unwrap();
+
+    let days = replay_snapshots(&dir, &[date]).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(days[0].attempted, [ZacksParser::NAME]);
+    assert_eq!(days[0].succeeded, [ZacksParser::NAME]);
+    assert!(days[0].events.is_empty());
 }
@@ -1,17 +1,24 @@
 use async_trait::async_trait;
+use chrono::NaiveDate;
 use std::vec;
 use thirtyfour::{prelude::ElementQueryable, By, WebDriver};
 
 use super::{
-    Company, WebsiteParser, LOAD_WAIT, SCROLL_INTO_VIEW, TIMEOUT_FIVE_SEC,
+    event::select_text, selector, Company, EarningsEvent, Navigation,
+    ReportTiming, WebsiteParser, LOAD_WAIT, SCROLL_INTO_VIEW, TIMEOUT_FIVE_SEC,
     TRADINGVIEW, WAIT_INTERVAL,
 };
-use crate::RelativeDay;
 
 const DAY_SELECTOR: &str = "div[class=\"itemContent-LeZwGiB6\"]";
 const SYMBOL_SELECTOR: &str =
     "a[class=\"tv-screener__symbol apply-common-tooltip\"]";
 const COMPANY_NAME_SELECTOR: &str = "span[class=\"tv-screener__description\"]";
+const ROW_SELECTOR: &str = "tbody>tr";
+const MARKET_CAP_SELECTOR: &str = "td:nth-child(2)";
+const EPS_ESTIMATE_SELECTOR: &str = "td:nth-child(3)";
+const REVENUE_ESTIMATE_SELECTOR: &str = "td:nth-child(4)";
+const PERIOD_ENDING_SELECTOR: &str = "td:nth-child(5)";
+const TIME_SELECTOR: &str = "td:nth-child(6)";
 
 pub struct TradingViewParser {}
 
@@ -19,21 +26,80 @@ pub struct TradingViewParser {}
 impl WebsiteParser for TradingViewParser {
     const NAME: &'static str = "TradingView";
 
-    async fn parse(
+    /// The calendar only has buttons for the nearest days.
+    fn lists(date: NaiveDate) -> bool {
+        let today = chrono::offset::Local::now().date_naive();
+        (-1..=1).contains(&(date - today).num_days())
+    }
+
+    async fn navigate(
         driver: &WebDriver,
-        day: RelativeDay,
-    ) -> anyhow::Result<Vec<Company>> {
+        date: NaiveDate,
+    ) -> anyhow::Result<Navigation> {
+        anyhow::ensure!(
+            Self::lists(date),
+            "{} only lists earnings from yesterday to tomorrow",
+            Self::NAME
+        );
         driver.goto(TRADINGVIEW).await?;
 
-        match day {
-            RelativeDay::Yesterday => to_previous_day(driver).await?,
-            RelativeDay::Tomorrow => to_next_day(driver).await?,
+        let today = chrono::offset::Local::now().date_naive();
+        match (date - today).num_days() {
+            -1 => to_previous_day(driver).await?,
+            1 => to_next_day(driver).await?,
             _ => (),
         }
         // Wait for the browser to load data table
         tokio::time::sleep(LOAD_WAIT).await;
 
-        parse_data(driver).await
+        Ok(Navigation::Loaded)
+    }
+
+    fn extract(
+        html: &str,
+        _date: NaiveDate,
+    ) -> anyhow::Result<Vec<EarningsEvent>> {
+        let document = scraper::Html::parse_document(html);
+        let row_selector = selector(ROW_SELECTOR)?;
+        let symbol_selector = selector(SYMBOL_SELECTOR)?;
+        let names_selector = selector(COMPANY_NAME_SELECTOR)?;
+        let market_cap_selector = selector(MARKET_CAP_SELECTOR)?;
+        let eps_estimate_selector = selector(EPS_ESTIMATE_SELECTOR)?;
+        let revenue_estimate_selector = selector(REVENUE_ESTIMATE_SELECTOR)?;
+        let period_ending_selector = selector(PERIOD_ENDING_SELECTOR)?;
+        let time_selector = selector(TIME_SELECTOR)?;
+
+        let mut events = Vec::new();
+        for row in document.select(&row_selector) {
+            let Some(symbol) = row.select(&symbol_selector).next() else {
+                continue;
+            };
+            let symbol = symbol.inner_html();
+            // The description is followed by additional elements
+            // so only the leading text is kept.
+            let name = row
+                .select(&names_selector)
+                .next()
+                .map(|e| {
+                    e.inner_html().split('<').next().unwrap().trim().to_string()
+                })
+                .ok_or_else(|| {
+                    anyhow::anyhow!("Found no company name for '{symbol}'")
+                })?;
+
+            let mut event =
+                EarningsEvent::new(Self::NAME, Company { symbol, name });
+            event.timing = select_text(row, &time_selector)
+                .map_or(ReportTiming::Unknown, |t| ReportTiming::from_text(&t));
+            event.fiscal_period = select_text(row, &period_ending_selector);
+            event.eps_estimate = select_text(row, &eps_estimate_selector);
+            event.revenue_estimate =
+                select_text(row, &revenue_estimate_selector);
+            event.market_cap = select_text(row, &market_cap_selector);
+            events.push(event);
+        }
+
+        Ok(events)
     }
 }
 
@@ -67,44 +133,23 @@ async fn to_next_day(driver: &WebDriver) -> anyhow::Result<()> {
     Ok(())
 }
 
-async fn parse_data(driver: &WebDriver) -> anyhow::Result<Vec<Company>> {
-    let source = driver.source().await?;
-    let document = scraper::Html::parse_document(&source);
-    let symbol_selector = scraper::Selector::parse(SYMBOL_SELECTOR)
-        .map_err(|e| eprintln!("{e}"))
-        .unwrap();
-    let names_selector = scraper::Selector::parse(COMPANY_NAME_SELECTOR)
-        .map_err(|e| eprintln!("{e}"))
-        .unwrap();
-    let symbols: Vec<String> = document
-        .select(&symbol_selector)
-        .map(|e| e.inner_html())
-        .collect();
-    let names_html: Vec<String> = document
-        .select(&names_selector)
-        .map(|e| e.inner_html())
-        .collect();
-    let names: Vec<String> = names_html
-        .iter()
-        .map(|n| {
-            n.split('<')
-                .collect::<Vec<&str>>()
-                .first()
-                .unwrap()
-                .trim()
-                .to_string()
-        })
-        .collect();
-    assert_eq!(symbols.len(), names_html.len());
-
-    let companies: Vec<Company> = symbols
-        .iter()
-        .zip(names.iter())
-        .map(|(s, n)| Company {
-            symbol: s.clone(),
-            name: n.clone(),
-        })
-        .collect();
-
-    Ok(companies)
+#[test]
+fn extract_fixture() {
+    let html = include_str!("fixtures/tradingview.html");
+    let date = NaiveDate::from_ymd_opt(2023, 4, 18).unwrap();
+    let events = TradingViewParser::extract(html, date).unwrap();
+    assert_eq!(
+        events
+            .iter()
+            .map(|e| (e.company.symbol.as_str(), e.company.name.as_str()))
+            .collect::<Vec<_>>(),
+        [("NFLX", "Netflix, Inc."), ("JNJ", "Johnson and Johnson")]
+    );
+    let nflx = &events[0];
+    assert_eq!(nflx.timing, ReportTiming::AfterClose);
+    assert_eq!(nflx.market_cap.as_deref(), Some("149.55B"));
+    assert_eq!(nflx.eps_estimate.as_deref(), Some("2.86"));
+    assert_eq!(nflx.revenue_estimate.as_deref(), Some("8.18B"));
+    assert_eq!(nflx.fiscal_period.as_deref(), Some("2023-03-31"));
+    assert_eq!(events[1].timing, ReportTiming::BeforeOpen);
 }
@@ -1,28 +1,33 @@
 use async_trait::async_trait;
-use chrono::{Datelike, Days, NaiveDate, Weekday};
+use chrono::{Datelike, Days, NaiveDate};
 use std::vec;
 use thirtyfour::{prelude::ElementQueryable, By, WebDriver};
 
 use super::{
-    Company, WebsiteParser, SCROLL_INTO_VIEW, TIMEOUT_FIVE_SEC,
+    event::select_text, selector, Company, EarningsEvent, Navigation,
+    ReportTiming, WebsiteParser, SCROLL_INTO_VIEW, TIMEOUT_FIVE_SEC,
     TIMEOUT_TEN_SEC, WAIT_INTERVAL, ZACKS,
 };
-use crate::{parser::LOAD_WAIT_SHORT, RelativeDay};
+use crate::parser::LOAD_WAIT_SHORT;
 
 const PREVIOUS_WEEK_SELECTOR: &str = "div[class=\"prenext_txt align_left\"]>a";
 const NEXT_WEEK_SELECTOR: &str = "div[class=\"prenext_txt align_right\"]>a";
 const SHOW_ENTRIES_SELECTOR: &str =
     "div#earnings_rel_data_all_table_length>label>select";
-const SYMBOL_SELECTOR: &str =
-    "table#earnings_rel_data_all_table>tbody>tr>th>a>span";
-const COMPANY_NAME_SELECTOR: &str =
-    "table#earnings_rel_data_all_table>tbody>tr>td:nth-child(2)>span";
+const ROW_SELECTOR: &str = "table#earnings_rel_data_all_table>tbody>tr";
+const SYMBOL_SELECTOR: &str = "th>a>span";
+const COMPANY_NAME_SELECTOR: &str = "td:nth-child(2)>span";
+const MARKET_CAP_SELECTOR: &str = "td:nth-child(3)";
+const TIME_SELECTOR: &str = "td:nth-child(4)";
+const ESTIMATE_SELECTOR: &str = "td:nth-child(5)";
+const REPORTED_SELECTOR: &str = "td:nth-child(6)";
 const SHOW_ALL_BUTTON_SELECTOR: &str = "option[value=\"-1\"]";
 const ACCEPT_BUTTON_CSS: &str =
     "button[class=\"Button__StyledButton-a1qza5-0 fLZgds\"]";
-const COOKIE_ACCEPT_CSS: &str = "button[id=\"accept_cookie\"";
+const COOKIE_ACCEPT_CSS: &str = "button[id=\"accept_cookie\"]";
 const EVENTS_TITLE: &str = "WeeklyEventsTitle";
-const READ_MODE_BUTTON_CSS: &str = "button[class=\"Button__StyledButton-a1qza5-0 fLZgds\"]";
+const READ_MODE_BUTTON_CSS: &str =
+    "button[class=\"Button__StyledButton-a1qza5-0 fLZgds\"]";
 
 pub struct ZacksParser {}
 
@@ -30,35 +35,128 @@ pub struct ZacksParser {}
 impl WebsiteParser for ZacksParser {
     const NAME: &'static str = "Zacks";
 
-    async fn parse(
+    async fn navigate(
         driver: &WebDriver,
-        day: RelativeDay,
-    ) -> anyhow::Result<Vec<Company>> {
-        driver.goto(ZACKS).await?;
-        // Accept cookies in order to remove the cookies 'obstacle' dialog box.
-        accept_cookies(driver).await.unwrap_or(());
+        date: NaiveDate,
+    ) -> anyhow::Result<Navigation> {
+        open_week(driver, date).await?;
+        show_earnings(driver, date).await
+    }
 
-        let today = chrono::offset::Local::now().date_naive();
-        let target = day.get_date();
-        let target_weekday = target.weekday();
-        // Weekdays here are counted from Sun to Sat.
-        match today.weekday() {
-            // Check if the target week is before the today week
-            Weekday::Sun => {
-                if target_weekday == Weekday::Sat {
-                    to_previous_week(driver).await?;
-                }
-            }
-            // Check if the target week is after the today week
-            Weekday::Sat => {
-                if target_weekday == Weekday::Sun {
-                    to_next_week(driver).await?;
-                }
+    async fn load_week(
+        driver: &WebDriver,
+        dates: &[NaiveDate],
+    ) -> anyhow::Result<Vec<Option<String>>> {
+        // The week page links to the earnings of every day in the week.
+        let mut shown_week = None;
+        let mut pages = Vec::with_capacity(dates.len());
+        for &date in dates {
+            if shown_week != Some(week_start(date)) {
+                open_week(driver, date).await?;
+                shown_week = Some(week_start(date));
             }
-            _ => (),
+            let page = match show_earnings(driver, date).await? {
+                Navigation::Loaded => Some(driver.source().await?),
+                Navigation::Empty => None,
+            };
+            pages.push(page);
         }
-        parse_data(driver, target).await
+        Ok(pages)
     }
+
+    fn extract(
+        html: &str,
+        _date: NaiveDate,
+    ) -> anyhow::Result<Vec<EarningsEvent>> {
+        let document = scraper::Html::parse_document(html);
+        let row_selector = selector(ROW_SELECTOR)?;
+        let symbol_selector = selector(SYMBOL_SELECTOR)?;
+        let names_selector = selector(COMPANY_NAME_SELECTOR)?;
+        let market_cap_selector = selector(MARKET_CAP_SELECTOR)?;
+        let time_selector = selector(TIME_SELECTOR)?;
+        let estimate_selector = selector(ESTIMATE_SELECTOR)?;
+        let reported_selector = selector(REPORTED_SELECTOR)?;
+
+        let mut events = Vec::new();
+        for row in document.select(&row_selector) {
+            // Rows without a symbol don't hold any data.
+            let Some(symbol) = row.select(&symbol_selector).next() else {
+                continue;
+            };
+            // Symbols may contain additional '<span>' elements after the text.
+            let symbol =
+                symbol.inner_html().split('<').next().unwrap().to_string();
+            let name = row
+                .select(&names_selector)
+                .next()
+                .map(|e| e.inner_html())
+                .ok_or_else(|| {
+                    anyhow::anyhow!("Found no company name for '{symbol}'")
+                })?;
+
+            let mut event =
+                EarningsEvent::new(Self::NAME, Company { symbol, name });
+            event.timing = select_text(row, &time_selector)
+                .map_or(ReportTiming::Unknown, |t| ReportTiming::from_text(&t));
+            event.eps_estimate = select_text(row, &estimate_selector);
+            event.eps_reported = select_text(row, &reported_selector);
+            event.market_cap = select_text(row, &market_cap_selector);
+            events.push(event);
+        }
+
+        Ok(events)
+    }
+}
+
+/// Opens the website and moves the calendar from the today week
+/// to the week containing the `date`.
+async fn open_week(driver: &WebDriver, date: NaiveDate) -> anyhow::Result<()> {
+    driver.goto(ZACKS).await?;
+    // Accept cookies in order to remove the cookies 'obstacle' dialog box.
+    accept_cookies(driver).await.unwrap_or(());
+
+    let today = chrono::offset::Local::now().date_naive();
+    let weeks = (week_start(date) - week_start(today)).num_weeks();
+    let mut shown = week_start(today);
+    for _ in 0..weeks.abs() {
+        if weeks < 0 {
+            to_previous_week(driver).await?;
+            shown = shown.checked_sub_days(Days::new(7)).unwrap();
+        } else {
+            to_next_week(driver).await?;
+            shown = shown.checked_add_days(Days::new(7)).unwrap();
+        }
+        wait_for_week(driver, shown).await?;
+    }
+    Ok(())
+}
+
+/// Weeks here are counted from Sun to Sat.
+fn week_start(date: NaiveDate) -> NaiveDate {
+    let weekday_num = date.weekday().num_days_from_sunday() as u64;
+    date.checked_sub_days(Days::new(weekday_num)).unwrap()
+}
+
+/// Waits until the calendar shows the week containing the `date`.
+async fn wait_for_week(
+    driver: &WebDriver,
+    date: NaiveDate,
+) -> anyhow::Result<()> {
+    let week_start = week_start(date);
+    let week_end = week_start.checked_add_days(Days::new(6)).unwrap();
+    let header_text = format!(
+        "Events For {} - {}",
+        week_start.format("%-m/%-d/%Y"),
+        week_end.format("%-m/%-d/%Y")
+    );
+    driver
+        .query(By::Id(EVENTS_TITLE))
+        .with_text(header_text)
+        .wait(TIMEOUT_TEN_SEC, WAIT_INTERVAL)
+        .desc("Wait for the week events to load")
+        .single()
+        .await?;
+    Ok(())
 }
 
 async fn to_previous_week(driver: &WebDriver) -> anyhow::Result<()> {
@@ -90,13 +188,15 @@ async fn to_next_week(driver: &WebDriver) -> anyhow::Result<()> {
 }
 
 async fn accept_cookies(driver: &WebDriver) -> anyhow::Result<()> {
-    if let Ok(button) = driver.query(By::Css(READ_MODE_BUTTON_CSS))
-    .wait(TIMEOUT_TEN_SEC, WAIT_INTERVAL)
-    .desc("Find the 'Read more' button")
-    .single()
-        .await {
-            button.click().await?;
-        }
+    if let Ok(button) = driver
+        .query(By::Css(READ_MODE_BUTTON_CSS))
+        .wait(TIMEOUT_TEN_SEC, WAIT_INTERVAL)
+        .desc("Find the 'Read more' button")
+        .single()
+        .await
+    {
+        button.click().await?;
+    }
     driver
         .query(By::Css(ACCEPT_BUTTON_CSS))
         .wait(TIMEOUT_TEN_SEC, WAIT_INTERVAL)
@@ -116,33 +216,20 @@ async fn accept_cookies(driver: &WebDriver) -> anyhow::Result<()> {
     Ok(())
 }
 
-// TODO branch this function into smaller functions.
-async fn parse_data(
+/// Opens the earnings table for the target `date` and
+/// makes it show all entries.
+async fn show_earnings(
     driver: &WebDriver,
     date: NaiveDate,
-) -> anyhow::Result<Vec<Company>> {
+) -> anyhow::Result<Navigation> {
     // Calculate and create needed header IDs.
-    let weekday = date.weekday();
-    let weekday_num = weekday.num_days_from_sunday() as u64;
-    let week_start = date.checked_sub_days(Days::new(weekday_num)).unwrap();
-    let week_end = date.checked_add_days(Days::new(6 - weekday_num)).unwrap();
-    let header_text = format!(
-        "Events For {} - {}",
-        week_start.format("%-m/%-d/%Y"),
-        week_end.format("%-m/%-d/%Y")
-    );
+    let weekday_num = date.weekday().num_days_from_sunday();
     let header_id = format!("d_{}", weekday_num);
     let earnings_link_id = format!("cal_link_{}", weekday_num);
     let earnings_selector_css =
         format!("a#{}[evt_type=\"1\"]", earnings_link_id);
-    
 
-    driver
-        .query(By::Id(EVENTS_TITLE))
-        .with_text(header_text)
-        .wait(TIMEOUT_TEN_SEC, WAIT_INTERVAL)
-        .single()
-        .await?;
+    wait_for_week(driver, date).await?;
     if let Ok(e) = driver
         .query(By::Css(&earnings_selector_css))
         .wait(TIMEOUT_FIVE_SEC, WAIT_INTERVAL)
@@ -165,7 +252,7 @@ async fn parse_data(
             break;
         }
     } else {
-        return Ok(vec![]);
+        return Ok(Navigation::Empty);
     }
     tokio::time::sleep(LOAD_WAIT_SHORT).await;
     let selector = driver
@@ -187,38 +274,7 @@ async fn parse_data(
         .click()
         .await?;
 
-    let source = driver.source().await?;
-    let document = scraper::Html::parse_document(&source);
-    let symbol_selector = scraper::Selector::parse(SYMBOL_SELECTOR)
-        .map_err(|e| eprintln!("{e}"))
-        .unwrap();
-    let names_selector = scraper::Selector::parse(COMPANY_NAME_SELECTOR)
-        .map_err(|e| eprintln!("{e}"))
-        .unwrap();
-    let mut symbols: Vec<String> = document
-        .select(&symbol_selector)
-        .map(|e| e.inner_html())
-        .collect();
-    let names: Vec<String> = document
-        .select(&names_selector)
-        .map(|e| e.inner_html())
-        .collect();
-
-    for sym in symbols.iter_mut() {
-        *sym = sym.split('<').collect::<Vec<&str>>()[0].to_string();
-    }
-    assert_eq!(symbols.len(), names.len());
-
-    let companies: Vec<Company> = symbols
-        .iter()
-        .zip(names.iter())
-        .map(|(s, n)| Company {
-            symbol: s.clone(),
-            name: n.clone(),
-        })
-        .collect();
-
-    Ok(companies)
+    Ok(Navigation::Loaded)
 }
 
 #[test]
@@ -301,3 +357,65 @@ fn week_range_dates() {
         "Events For 4/30/2023 - 5/6/2023"
     );
 }
+
+#[test]
+fn extract_fixture() {
+    let html = include_str!("fixtures/zacks.html");
+    let date = NaiveDate::from_ymd_opt(2023, 4, 18).unwrap();
+    let events = ZacksParser::extract(html, date).unwrap();
+    assert_eq!(
+        events
+            .iter()
+            .map(|e| (e.company.symbol.as_str(), e.company.name.as_str()))
+            .collect::<Vec<_>>(),
+        [
+            ("JNJ", "Johnson And Johnson"),
+            ("NFLX", "Netflix, Inc."),
+            ("BRK.B", "Berkshire Hathaway Inc."),
+        ]
+    );
+    let jnj = &events[0];
+    assert_eq!(jnj.timing, ReportTiming::BeforeOpen);
+    assert_eq!(jnj.market_cap.as_deref(), Some("433,185.13"));
+    assert_eq!(jnj.eps_estimate.as_deref(), Some("2.50"));
+    assert_eq!(jnj.eps_reported, None);
+    assert_eq!(events[1].timing, ReportTiming::AfterClose);
+    assert_eq!(events[2].timing, ReportTiming::Unknown);
+}
+
+#[test]
+fn extract_reported_eps() {
+    let html = r#"<table id="earnings_rel_data_all_table"><tbody>
+        <tr><th><a><span>JNJ</span></a></th>
+        <td><span>Johnson And Johnson</span></td><td>433,185.13</td>
+        <td>Before Open</td><td>2.50</td><td>2.68</td></tr>
+        </tbody></table>"#;
+    let date = NaiveDate::from_ymd_opt(2023, 4, 18).unwrap();
+    let events = ZacksParser::extract(html, date).unwrap();
+    assert_eq!(events[0].eps_reported.as_deref(), Some("2.68"));
+}
+
+#[test]
+fn extract_mismatched_columns() {
+    // A symbol without its company name cell.
+    let html = r#"<table id="earnings_rel_data_all_table"><tbody>
+        <tr><th><a><span>JNJ</span></a></th><td></td></tr>
+        </tbody></table>"#;
+    let date = NaiveDate::from_ymd_opt(2023, 4, 18).unwrap();
+    assert!(ZacksParser::extract(html, date).is_err());
+}
+
+#[test]
+fn weeks_between_dates() {
+    let date = NaiveDate::from_ymd_opt(2023, 4, 22).unwrap();
+    assert_eq!(
+        week_start(date),
+        NaiveDate::from_ymd_opt(2023, 4, 16).unwrap()
+    );
+
+    let today = NaiveDate::from_ymd_opt(2023, 4, 18).unwrap();
+    let target = NaiveDate::from_ymd_opt(2023, 4, 30).unwrap();
+    assert_eq!((week_start(target) - week_start(today)).num_weeks(), 2);
+    let target = NaiveDate::from_ymd_opt(2023, 4, 15).unwrap();
+    assert_eq!((week_start(target) - week_start(today)).num_weeks(), -1);
+}
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use crate::{candidate::CompanyCandidate, history::SourceStats};
+
+/// Weight of websites without a configured or learned weight.
+const DEFAULT_WEIGHT: f64 = 1.0;
+/// Reconciled listings of a website needed before its precision is
+/// trusted over the other weights.
+const MIN_RECONCILED: usize = 20;
+
+/// Reliability weights of the websites, used for scoring
+/// the candidates.
+#[derive(Debug, Default)]
+pub struct SourceWeights {
+    /// Lowercase website name mapped to its weight.
+    weights: HashMap<String, f64>,
+}
+
+impl SourceWeights {
+    /// Creates the weights from website names (in any case) mapped
+    /// to their weights.
+    pub fn new(weights: &HashMap<String, f64>) -> Self {
+        let weights = weights
+            .iter()
+            .map(|(source, weight)| (source.to_lowercase(), *weight))
+            .collect();
+        Self { weights }
+    }
+
+    /// Replaces the weights of the websites in `learned`.
+    pub fn extend(&mut self, learned: HashMap<String, f64>) {
+        for (source, weight) in learned {
+            self.weights.insert(source.to_lowercase(), weight);
+        }
+    }
+
+    /// Replaces the weights with the `agreement` rates of the websites,
+    /// or with their precision in the `stats` once enough of their
+    /// listings were reconciled, since confirmed listings are better
+    /// evidence than agreement.
+    pub fn learn(
+        &mut self,
+        agreement: HashMap<String, f64>,
+        stats: &[SourceStats],
+    ) {
+        self.extend(agreement);
+        self.extend(
+            stats
+                .iter()
+                .filter(|s| s.predicted >= MIN_RECONCILED)
+                .map(|s| (s.source.clone(), s.precision()))
+                .collect(),
+        );
+    }
+
+    pub fn weight(&self, source: &str) -> f64 {
+        self.weights
+            .get(&source.to_lowercase())
+            .copied()
+            .unwrap_or(DEFAULT_WEIGHT)
+    }
+
+    /// Confidence of the candidate, the weight of the websites listing
+    /// it divided by the weight of all `succeeded` websites, in range
+    /// from 0 to 1.
+    pub fn score(
+        &self,
+        candidate: &CompanyCandidate,
+        succeeded: &[&'static str],
+    ) -> f64 {
+        let sources = candidate.sources();
+        let listed: f64 = sources.iter().map(|s| self.weight(s)).sum();
+        let total: f64 = succeeded
+            .iter()
+            .chain(sources.iter().filter(|s| !succeeded.contains(s)))
+            .map(|s| self.weight(s))
+            .sum();
+        if total > 0.0 {
+            listed / total
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Scores the `candidates` of a date with the `weights`, removes
+/// the ones scoring less than `min_score` and sorts the rest by their
+/// score, references and symbol.
+pub fn score_candidates(
+    candidates: &mut Vec<CompanyCandidate>,
+    weights: &SourceWeights,
+    succeeded: &[&'static str],
+    min_score: f64,
+) {
+    for candidate in candidates.iter_mut() {
+        candidate.score = weights.score(candidate, succeeded);
+    }
+    candidates.retain(|c| c.score >= min_score);
+    candidates.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(b.refs.cmp(&a.refs))
+            .then_with(|| a.event.company.symbol.cmp(&b.event.company.symbol))
+    });
+}
+
+/// Parses the minimum score, which must be in range from 0 to 1.
+pub fn parse_score(score: &str) -> Result<f64, String> {
+    let score: f64 = score.parse().map_err(|e| format!("{e}"))?;
+    if (0.0..=1.0).contains(&score) {
+        Ok(score)
+    } else {
+        Err("expected a score from 0 to 1".to_owned())
+    }
+}
+
+#[test]
+fn weighted_scores() {
+    use crate::{
+        candidate::{eval_candidates, event},
+        symbol::SymbolNormalizer,
+    };
+
+    let candidates = eval_candidates(
+        vec![
+            event("Zacks", "NFLX", ""),
+            event("Benzinga", "NFLX", ""),
+            event("Benzinga", "IBKR", ""),
+        ],
+        1,
+        &SymbolNormalizer::default(),
+    );
+    let succeeded = ["Zacks", "Benzinga", "Investing"];
+
+    let weights = SourceWeights::default();
+    assert!(
+        (weights.score(&candidates[0], &succeeded) - 2.0 / 3.0).abs() < 1e-9
+    );
+
+    let weights = SourceWeights::new(&HashMap::from([
+        ("benzinga".to_owned(), 0.5),
+        ("Investing".to_owned(), 1.5),
+    ]));
+    assert_eq!(weights.weight("Benzinga"), 0.5);
+    assert_eq!(weights.weight("Zacks"), 1.0);
+    assert_eq!(weights.score(&candidates[0], &succeeded), 0.5);
+    assert_eq!(weights.score(&candidates[1], &succeeded), 0.5 / 3.0);
+
+    let mut candidates = candidates;
+    score_candidates(&mut candidates, &weights, &succeeded, 0.2);
+    assert_eq!(candidates.len(), 1);
+    assert_eq!(candidates[0].score, 0.5);
+}
+
+#[test]
+fn learned_weights() {
+    let stats = |source: &str, predicted, confirmed| SourceStats {
+        source: source.to_owned(),
+        predicted,
+        confirmed,
+        reported: 100,
+    };
+    let mut weights = SourceWeights::new(&HashMap::from([
+        ("Zacks".to_owned(), 0.8),
+        ("MarketWatch".to_owned(), 0.9),
+    ]));
+    weights.learn(
+        HashMap::from([
+            ("Benzinga".to_owned(), 0.7),
+            ("Investing".to_owned(), 0.6),
+        ]),
+        &[
+            stats("Benzinga", 40, 30),
+            stats("Investing", 3, 0),
+            stats("Zacks", 5, 0),
+        ],
+    );
+    assert_eq!(weights.weight("Benzinga"), 0.75);
+    // A few reconciled listings don't replace the other weights.
+    assert_eq!(weights.weight("Investing"), 0.6);
+    assert_eq!(weights.weight("Zacks"), 0.8);
+    assert_eq!(weights.weight("MarketWatch"), 0.9);
+}
+
+#[test]
+fn min_score_range() {
+    assert_eq!(parse_score("0.5"), Ok(0.5));
+    assert!(parse_score("1.5").is_err());
+    assert!(parse_score("-0.1").is_err());
+    assert!(parse_score("high").is_err());
+}
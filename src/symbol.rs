@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+
+/// HTML entities which can be left in symbols read with `inner_html()`.
+const HTML_ENTITIES: [(&str, &str); 6] = [
+    ("&amp;", "&"),
+    ("&lt;", "<"),
+    ("&gt;", ">"),
+    ("&quot;", "\""),
+    ("&#39;", "'"),
+    ("&nbsp;", " "),
+];
+
+/// Brings the ticker symbols of all websites to the same notation
+/// so the listings of the same company can be matched.
+#[derive(Debug, Default)]
+pub struct SymbolNormalizer {
+    /// Normalized symbol mapped to the symbol it should be replaced with.
+    aliases: HashMap<String, String>,
+}
+
+impl SymbolNormalizer {
+    /// Creates a normalizer replacing each symbol on the left side
+    /// of the `aliases` with the symbol on the right side.
+    /// Both sides are normalized first.
+    pub fn new(aliases: &HashMap<String, String>) -> Self {
+        let aliases = aliases
+            .iter()
+            .map(|(from, to)| (normalize(from), normalize(to)))
+            .collect();
+        Self { aliases }
+    }
+
+    /// Normalizes the `symbol` and replaces it with its alias, if any.
+    pub fn normalize(&self, symbol: &str) -> String {
+        let symbol = normalize(symbol);
+        match self.aliases.get(&symbol) {
+            Some(alias) => alias.clone(),
+            None => symbol,
+        }
+    }
+}
+
+/// Decodes HTML entities, removes the exchange prefix (e.g. `NYSE:`)
+/// and uses a dot as the share class separator, so `BRK-B`, `BRK/B`
+/// and `NYSE:brk.b ` all become `BRK.B`.
+fn normalize(symbol: &str) -> String {
+    let mut symbol = symbol.to_owned();
+    for (entity, character) in HTML_ENTITIES {
+        symbol = symbol.replace(entity, character);
+    }
+    let symbol = symbol.trim();
+    let symbol = match symbol.rsplit_once(':') {
+        Some((_exchange, symbol)) => symbol.trim(),
+        None => symbol,
+    };
+    symbol
+        .split(|c: char| c == '-' || c == '/' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(".")
+        .to_uppercase()
+}
+
+#[test]
+fn share_class_notations() {
+    let normalizer = SymbolNormalizer::default();
+    for symbol in ["BRK.B", "BRK-B", "BRK/B", "brk.b", "NYSE:BRK.B", "BRK B"] {
+        assert_eq!(normalizer.normalize(symbol), "BRK.B");
+    }
+}
+
+#[test]
+fn symbols_from_html() {
+    let normalizer = SymbolNormalizer::default();
+    assert_eq!(normalizer.normalize(" AAPL\n "), "AAPL");
+    assert_eq!(normalizer.normalize("AAPL&nbsp;"), "AAPL");
+    assert_eq!(normalizer.normalize("NASDAQ: MSFT"), "MSFT");
+    assert_eq!(normalizer.normalize("M&amp;T"), "M&T");
+}
+
+#[test]
+fn configured_aliases() {
+    let aliases = HashMap::from([("goog".to_owned(), "GOOGL".to_owned())]);
+    let normalizer = SymbolNormalizer::new(&aliases);
+    assert_eq!(normalizer.normalize("GOOG"), "GOOGL");
+    assert_eq!(normalizer.normalize("NASDAQ:GOOG"), "GOOGL");
+    assert_eq!(normalizer.normalize("GOOGL"), "GOOGL");
+}